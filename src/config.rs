@@ -0,0 +1,85 @@
+//! Scan settings committed to a project in a `todo2csv.toml` file.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Deserializer};
+
+use crate::output::Format;
+use crate::utils::{CommentSyntax, PathStyle};
+
+/// The settings in a `todo2csv.toml`. Each one that is left out keeps its default, and the
+/// command line overrides them all:
+///
+/// ```toml
+/// keywords = ["TODO", "FIXME"]
+/// exclude = ["**/generated/**"]
+/// include = ["src/**"]
+/// extensions = ["rs", "proto"]
+/// format = "json"
+/// path-style = "repo-relative"
+///
+/// [add-extensions]
+/// proto = "slashes"
+///
+/// [aliases]
+/// REVIEW = "TODO"
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct Config {
+    pub keywords: Option<Vec<String>>,
+    pub aliases: HashMap<String, String>,
+    pub exclude: Vec<String>,
+    pub include: Vec<String>,
+    pub extensions: Option<Vec<String>>,
+    pub add_extensions: BTreeMap<String, CommentSyntax>,
+    #[serde(deserialize_with = "format")]
+    pub format: Option<Format>,
+    #[serde(deserialize_with = "path_style")]
+    pub path_style: Option<PathStyle>,
+}
+
+impl Config {
+    pub const FILE_NAME: &'static str = "todo2csv.toml";
+
+    /// The `todo2csv.toml` in `dir` or the nearest directory above it that has one.
+    pub fn discover(dir: &Path) -> Option<PathBuf> {
+        let dir = dir.canonicalize().ok()?;
+        dir.ancestors()
+            .map(|dir| dir.join(Self::FILE_NAME))
+            .find(|path| path.is_file())
+    }
+
+    /// Reads the config at `path`. The error names the file, and for invalid settings the key
+    /// and its line.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text =
+            fs::read_to_string(path).map_err(|err| format!("{}: {}", path.display(), err))?;
+        toml::from_str(&text)
+            .map_err(|err| format!("{}: {}", path.display(), err.to_string().trim_end()))
+    }
+}
+
+fn format<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Format>, D::Error> {
+    let name = String::deserialize(deserializer)?;
+    match Format::from_name(&name) {
+        Some(format) => Ok(Some(format)),
+        None => Err(serde::de::Error::custom(format!(
+            "unknown format `{}`",
+            name
+        ))),
+    }
+}
+
+fn path_style<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<PathStyle>, D::Error> {
+    let name = String::deserialize(deserializer)?;
+    match PathStyle::from_name(&name) {
+        Some(style) => Ok(Some(style)),
+        None => Err(serde::de::Error::custom(format!(
+            "unknown path style `{}`",
+            name
+        ))),
+    }
+}
@@ -0,0 +1,126 @@
+//! Git metadata for the entries found by a [`Scanner`](crate::utils::Scanner).
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use chrono::{DateTime, FixedOffset, NaiveDate};
+
+/// Who last changed a line, when, and in which commit.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Blame {
+    /// The commit's hash, abbreviated to its first 7 digits.
+    pub commit: String,
+    pub author: String,
+    /// The author date of the commit, in the author's time zone.
+    pub date: NaiveDate,
+}
+
+/// Runs `git blame` once for the whole of `path`, keyed by 1-based line number. Lines with
+/// uncommitted changes are left out, and files outside a git work tree give an empty map.
+pub fn blame(path: &Path) -> HashMap<usize, Blame> {
+    let (Some(dir), Some(name)) = (path.parent(), path.file_name()) else {
+        return HashMap::new();
+    };
+    let dir = if dir.as_os_str().is_empty() {
+        Path::new(".")
+    } else {
+        dir
+    };
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(["blame", "--porcelain", "--"])
+        .arg(name)
+        .stderr(Stdio::null())
+        .output();
+    match output {
+        Ok(output) if output.status.success() => {
+            parse_porcelain(&String::from_utf8_lossy(&output.stdout))
+        }
+        _ => HashMap::new(),
+    }
+}
+
+/// The full hash of the commit checked out in the git work tree holding `dir`.
+pub fn head(dir: &Path) -> Option<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(["rev-parse", "HEAD"])
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+    let hash = String::from_utf8(output.stdout).ok()?;
+    output.status.success().then(|| hash.trim().to_string())
+}
+
+/// The fields of a commit that `git blame --porcelain` reports.
+#[derive(Default)]
+struct Commit<'a> {
+    author: Option<&'a str>,
+    time: Option<i64>,
+    tz: Option<&'a str>,
+}
+
+impl Commit<'_> {
+    fn blame(&self, hash: &str) -> Option<Blame> {
+        Some(Blame {
+            commit: hash.get(..7)?.to_string(),
+            author: self.author?.to_string(),
+            date: author_date(self.time?, self.tz?)?,
+        })
+    }
+}
+
+/// Parses `git blame --porcelain` output. The author of a commit is only given the first time
+/// the commit appears, so it is remembered for the later lines.
+fn parse_porcelain(porcelain: &str) -> HashMap<usize, Blame> {
+    let mut commits: HashMap<&str, Commit> = HashMap::new();
+    // The commit and line number of the header being read.
+    let mut current = None;
+    let mut lines = HashMap::new();
+    for line in porcelain.lines() {
+        if line.starts_with('\t') {
+            if let Some((hash, number)) = current.take() {
+                if let Some(blame) = commits.get(hash).and_then(|commit| commit.blame(hash)) {
+                    lines.insert(number, blame);
+                }
+            }
+            continue;
+        }
+        let (key, value) = line.split_once(' ').unwrap_or((line, ""));
+        if key.len() == 40 && key.bytes().all(|b| b.is_ascii_hexdigit()) {
+            // The all-zero commit stands for changes that aren't committed yet.
+            let committed = key.bytes().any(|b| b != b'0');
+            let number = value.split(' ').nth(1).and_then(|n| n.parse().ok());
+            current = number.filter(|_| committed).map(|number| (key, number));
+            commits.entry(key).or_default();
+            continue;
+        }
+        let Some(commit) = current.and_then(|(hash, _)| commits.get_mut(hash)) else {
+            continue;
+        };
+        match key {
+            "author" => commit.author = Some(value),
+            "author-time" => commit.time = value.parse().ok(),
+            "author-tz" => commit.tz = Some(value),
+            _ => {}
+        }
+    }
+    lines
+}
+
+/// The date of a Unix `time` in a `+hhmm` time zone.
+fn author_date(time: i64, tz: &str) -> Option<NaiveDate> {
+    let sign = match tz.as_bytes().first()? {
+        b'+' => 1,
+        b'-' => -1,
+        _ => return None,
+    };
+    let hours: i32 = tz.get(1..3)?.parse().ok()?;
+    let minutes: i32 = tz.get(3..5)?.parse().ok()?;
+    let offset = FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60))?;
+    let time = DateTime::from_timestamp(time, 0)?;
+    Some(time.with_timezone(&offset).date_naive())
+}
@@ -1,175 +1,5346 @@
+pub mod config;
+pub mod git;
+pub mod output;
+
 pub mod utils {
     use lazy_static::lazy_static;
     use std::{
+        collections::{BTreeMap, HashMap, VecDeque},
+        ffi::OsStr,
         fs::File,
         io::{self, BufRead, BufReader},
-        path::Path,
+        path::{Path, PathBuf},
+        time::{Duration, Instant},
     };
 
-    use csv::Writer;
-    use regex::Regex;
+    use chrono::{DateTime, NaiveDate, Timelike, Utc};
+    use csv::Writer;
+    use regex::Regex;
+    use serde::{Deserialize, Serialize, Serializer};
+    use serde_json::Value;
+
+    use crate::git::{self, Blame};
+    use crate::output::{Column, TodoWriter};
+
+    lazy_static! {
+        static ref DEFAULT_SCANNER: Scanner = Scanner::default();
+        static ref PRIORITY_PATTERN: Regex = Regex::new(r"^P\d+$").unwrap();
+        static ref DATE_PATTERN: Regex = Regex::new(r"^\d{4}-\d{2}-\d{2}$").unwrap();
+        static ref DUE_PHRASE_PATTERN: Regex = Regex::new(r"\bby (\d{4}-\d{2}-\d{2})\b").unwrap();
+        static ref ISSUE_PATTERN: Regex = Regex::new(r"#\d+\b|\b[A-Z]{2,}-\d+\b").unwrap();
+        static ref RUST_MACRO_PATTERN: Regex = Regex::new(
+            r#"\b(?P<tag>todo!|unimplemented!)\s*\(\s*(?:"(?P<text>(?:[^"\\]|\\.)*)")?"#
+        )
+        .unwrap();
+    }
+
+    /// The keywords scanned for by default.
+    pub fn default_keywords() -> &'static [&'static str] {
+        &["TODO", "FIXME", "HACK", "XXX", "NOTE", "BUG", "OPTIMIZE"]
+    }
+
+    /// Builds the pattern for a comment `style` matching any of `keywords`. The keyword must be
+    /// followed by `:`, `-` or whitespace, so that identifiers such as `TODOLIST` don't match.
+    /// Matches a TODO comment at the start of a line or, if `inline`, after whitespace anywhere.
+    fn todo_pattern(
+        style: CommentStyle,
+        keywords: &[String],
+        ignore_case: bool,
+        inline: bool,
+    ) -> Regex {
+        let keywords = keywords
+            .iter()
+            .map(|keyword| match regex::escape(keyword) {
+                keyword if keyword == "TODO" && style.ignores_keyword_case() => {
+                    format!("(?i:{})", keyword)
+                }
+                keyword => keyword,
+            })
+            .collect::<Vec<_>>()
+            .join("|");
+        let keywords = if ignore_case {
+            format!("(?i:{})", keywords)
+        } else {
+            keywords
+        };
+        Regex::new(&format!(
+            r"(?m){}(?:{})\s*(?P<tag>{})(?P<bangs>!*)(?:\s*\((?P<author>[^)]*)\))?{}\s*(?:-\s+)?(?P<text>.*?\S)\s*{}$",
+            if inline { r"(?:^|\s)" } else { r"^\s*" },
+            style.prefix(),
+            keywords,
+            style.separator(),
+            style.suffix()
+        ))
+        .unwrap()
+    }
+
+    /// Matches a comment-only line in `style`, capturing its text.
+    fn continuation_pattern(style: CommentStyle) -> Regex {
+        Regex::new(&format!(
+            r"^\s*(?:{})\s*(?P<text>.*?\S)\s*{}$",
+            style.prefix(),
+            style.suffix()
+        ))
+        .unwrap()
+    }
+
+    /// The comment syntaxes a TODO can be written in.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    enum CommentStyle {
+        /// `//`, `///`, `//!` and single-line `/* */`. Doc comments set the `doc` group.
+        Slashes,
+        /// `#`.
+        Hash,
+        /// `--`.
+        Dashes,
+        /// `;`, `;;`.
+        Semicolon,
+        /// `%`, `%%`.
+        Percent,
+        /// Fortran `!`, matching `TODO` in any case.
+        Bang,
+        /// Vim script `"`.
+        Quote,
+        /// Single-line `<!-- -->`.
+        Html,
+        /// Single-line PowerShell `<# #>`.
+        HashBlock,
+        /// Unchecked Markdown task list items such as `- [ ] TODO: text`.
+        TaskList,
+        /// Javadoc-style `* @todo text` block tags, matching `TODO` in any case.
+        DocTag,
+        /// The lines inside a multi-line `/* */` comment, with or without a leading `*`.
+        BlockInterior,
+        /// The lines of a Python docstring, which need a `:` after the keyword since they are
+        /// prose rather than comments.
+        Docstring,
+    }
+
+    impl CommentStyle {
+        const ALL: [Self; 13] = [
+            Self::Slashes,
+            Self::Hash,
+            Self::Dashes,
+            Self::Semicolon,
+            Self::Percent,
+            Self::Bang,
+            Self::Quote,
+            Self::Html,
+            Self::HashBlock,
+            Self::TaskList,
+            Self::DocTag,
+            Self::BlockInterior,
+            Self::Docstring,
+        ];
+
+        /// Pattern matching the comment leader.
+        fn prefix(&self) -> &'static str {
+            match self {
+                Self::Slashes => r"(?P<doc>//[/!])|//|(?P<block>/\*)",
+                Self::Hash => "#",
+                Self::Dashes => "--+",
+                Self::Semicolon => ";+",
+                Self::Percent => "%+",
+                Self::Bang => "!+",
+                Self::Quote => "\"",
+                Self::Html => "<!--",
+                Self::HashBlock => "<#",
+                Self::TaskList => r"[-*+]\s+\[ \]",
+                Self::DocTag => r"(?:/\*\*|\*)\s*@",
+                Self::BlockInterior => r"\**",
+                Self::Docstring => r#"(?:[rRuU]?(?:"""|'''))?"#,
+            }
+        }
+
+        /// Pattern matching whatever may follow the comment text on the same line.
+        fn suffix(&self) -> &'static str {
+            match self {
+                Self::Slashes => r"(?:\*/\s*)?",
+                Self::DocTag | Self::BlockInterior => r"(?:\*/.*)?",
+                Self::Docstring => r#"(?:(?:"""|''').*)?"#,
+                Self::Html => "(?:-->.*)?",
+                Self::HashBlock => "(?:#>.*)?",
+                _ => "",
+            }
+        }
+
+        /// Pattern matching what separates the keyword from the comment text.
+        fn separator(&self) -> &'static str {
+            match self {
+                Self::Docstring => r"\s*:",
+                _ => r"(?:\s*[:-]|\s)",
+            }
+        }
+
+        /// Whether `TODO` matches in any case. The other keywords stay case-sensitive, so that
+        /// prose such as `! Note the loop order` isn't reported.
+        fn ignores_keyword_case(&self) -> bool {
+            matches!(self, Self::Bang | Self::DocTag)
+        }
+    }
+
+    /// The comment syntax of an extension added at runtime with [`Extensions::add`].
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+    #[serde(rename_all = "lowercase")]
+    pub enum CommentSyntax {
+        /// `//` and single-line `/* */`.
+        Slashes,
+        /// `#`.
+        Hash,
+        /// `--`.
+        Dashes,
+        /// `;`, `;;`.
+        Semicolon,
+        /// `%`, `%%`.
+        Percent,
+        /// Single-line `<!-- -->`.
+        Html,
+    }
+
+    impl CommentSyntax {
+        pub fn from_name(name: &str) -> Option<Self> {
+            match name {
+                "slashes" => Some(Self::Slashes),
+                "hash" => Some(Self::Hash),
+                "dashes" => Some(Self::Dashes),
+                "semicolon" => Some(Self::Semicolon),
+                "percent" => Some(Self::Percent),
+                "html" => Some(Self::Html),
+                _ => None,
+            }
+        }
+
+        fn comment_styles(&self) -> &'static [CommentStyle] {
+            match self {
+                Self::Slashes => &[CommentStyle::Slashes],
+                Self::Hash => &[CommentStyle::Hash],
+                Self::Dashes => &[CommentStyle::Dashes],
+                Self::Semicolon => &[CommentStyle::Semicolon],
+                Self::Percent => &[CommentStyle::Percent],
+                Self::Html => &[CommentStyle::Html],
+            }
+        }
+    }
+
+    /// A supported language, recognized by a file's extension or name.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum FileExtension {
+        Rust,
+        Python,
+        Java,
+        TypeScript,
+        JavaScript,
+        Go,
+        C,
+        Cpp,
+        Ruby,
+        Php,
+        CSharp,
+        Kotlin,
+        Swift,
+        Scala,
+        Shell,
+        Lua,
+        Haskell,
+        LiterateHaskell,
+        Sql,
+        Yaml,
+        Toml,
+        Html,
+        Xml,
+        Css,
+        Scss,
+        Less,
+        Markdown,
+        Dockerfile,
+        Makefile,
+        Justfile,
+        Perl,
+        Elixir,
+        Erlang,
+        Dart,
+        Vue,
+        Svelte,
+        R,
+        Notebook,
+        PowerShell,
+        Zig,
+        Clojure,
+        EmacsLisp,
+        CommonLisp,
+        VimScript,
+        Latex,
+        Fortran,
+        Groovy,
+        /// An extension added with [`Extensions::add`], which isn't in [`ALL`](Self::ALL).
+        Custom(CommentSyntax),
+    }
+
+    impl FileExtension {
+        /// Every language, in the order of the table in the README.
+        pub const ALL: [Self; 47] = [
+            Self::Rust,
+            Self::Python,
+            Self::Java,
+            Self::TypeScript,
+            Self::JavaScript,
+            Self::Go,
+            Self::C,
+            Self::Cpp,
+            Self::Ruby,
+            Self::Php,
+            Self::CSharp,
+            Self::Kotlin,
+            Self::Swift,
+            Self::Scala,
+            Self::Shell,
+            Self::Lua,
+            Self::Haskell,
+            Self::LiterateHaskell,
+            Self::Sql,
+            Self::Yaml,
+            Self::Toml,
+            Self::Html,
+            Self::Xml,
+            Self::Css,
+            Self::Scss,
+            Self::Less,
+            Self::Markdown,
+            Self::Dockerfile,
+            Self::Makefile,
+            Self::Justfile,
+            Self::Perl,
+            Self::Elixir,
+            Self::Erlang,
+            Self::Dart,
+            Self::Vue,
+            Self::Svelte,
+            Self::R,
+            Self::Notebook,
+            Self::PowerShell,
+            Self::Zig,
+            Self::Clojure,
+            Self::EmacsLisp,
+            Self::CommonLisp,
+            Self::VimScript,
+            Self::Latex,
+            Self::Fortran,
+            Self::Groovy,
+        ];
+
+        /// The language called `name`, as in the Language column but in any case, or else the
+        /// one with the extension `name`, so both `python` and `py` give [`Self::Python`].
+        pub fn from_name(name: &str) -> Option<Self> {
+            Self::ALL
+                .into_iter()
+                .find(|language| language.name().eq_ignore_ascii_case(name))
+                .or_else(|| Self::from_str(name))
+        }
+
+        fn from_str(ext: &str) -> Option<Self> {
+            match ext {
+                "rs" => Some(Self::Rust),
+                "py" => Some(Self::Python),
+                "java" => Some(Self::Java),
+                "ts" | "tsx" | "mts" | "cts" => Some(Self::TypeScript),
+                "js" | "jsx" | "mjs" | "cjs" => Some(Self::JavaScript),
+                "go" => Some(Self::Go),
+                "c" | "h" => Some(Self::C),
+                "cc" | "cpp" | "cxx" | "hpp" => Some(Self::Cpp),
+                "rb" | "rake" | "gemspec" => Some(Self::Ruby),
+                "php" | "phtml" => Some(Self::Php),
+                "cs" => Some(Self::CSharp),
+                "kt" | "kts" => Some(Self::Kotlin),
+                "swift" => Some(Self::Swift),
+                "scala" | "sbt" => Some(Self::Scala),
+                "sh" | "bash" | "zsh" | "fish" => Some(Self::Shell),
+                "lua" => Some(Self::Lua),
+                "hs" => Some(Self::Haskell),
+                "lhs" => Some(Self::LiterateHaskell),
+                "sql" => Some(Self::Sql),
+                "yml" | "yaml" => Some(Self::Yaml),
+                "toml" => Some(Self::Toml),
+                "html" | "htm" | "xhtml" => Some(Self::Html),
+                "xml" => Some(Self::Xml),
+                "css" => Some(Self::Css),
+                "scss" => Some(Self::Scss),
+                "less" => Some(Self::Less),
+                "md" | "mdx" => Some(Self::Markdown),
+                "pl" | "pm" => Some(Self::Perl),
+                "ex" | "exs" => Some(Self::Elixir),
+                "erl" | "hrl" => Some(Self::Erlang),
+                "dart" => Some(Self::Dart),
+                "vue" => Some(Self::Vue),
+                "svelte" => Some(Self::Svelte),
+                "R" | "r" => Some(Self::R),
+                "ipynb" => Some(Self::Notebook),
+                "ps1" | "psm1" | "psd1" => Some(Self::PowerShell),
+                "zig" => Some(Self::Zig),
+                "clj" | "cljs" | "cljc" | "edn" => Some(Self::Clojure),
+                "el" => Some(Self::EmacsLisp),
+                "lisp" => Some(Self::CommonLisp),
+                "vim" => Some(Self::VimScript),
+                "tex" | "sty" | "cls" | "bib" => Some(Self::Latex),
+                "f90" | "f95" | "f03" | "f08" => Some(Self::Fortran),
+                "groovy" | "gradle" => Some(Self::Groovy),
+                _ => None,
+            }
+        }
+
+        /// Recognizes well-known extensionless files by name, including dotfiles and suffixed
+        /// variants such as `Dockerfile.prod`.
+        fn from_filename(name: &OsStr) -> Option<Self> {
+            let name = name.to_str()?;
+            let name = name.strip_prefix('.').unwrap_or(name);
+            match name.split('.').next()? {
+                "Dockerfile" | "Containerfile" => Some(Self::Dockerfile),
+                "Makefile" | "makefile" | "GNUmakefile" => Some(Self::Makefile),
+                "Justfile" | "justfile" => Some(Self::Justfile),
+                "vimrc" | "gvimrc" | "_vimrc" => Some(Self::VimScript),
+                "Jenkinsfile" => Some(Self::Groovy),
+                _ => None,
+            }
+        }
+
+        pub fn from_path(path: &Path) -> Option<Self> {
+            path.extension()
+                .and_then(|ext| ext.to_str())
+                .and_then(Self::from_str)
+                .or_else(|| path.file_name().and_then(Self::from_filename))
+        }
+
+        /// The name of the language, as in the Language column.
+        pub fn name(&self) -> &'static str {
+            match self {
+                Self::Rust => "Rust",
+                Self::Python => "Python",
+                Self::Java => "Java",
+                Self::TypeScript => "TypeScript",
+                Self::JavaScript => "JavaScript",
+                Self::Go => "Go",
+                Self::C => "C",
+                Self::Cpp => "C++",
+                Self::Ruby => "Ruby",
+                Self::Php => "PHP",
+                Self::CSharp => "C#",
+                Self::Kotlin => "Kotlin",
+                Self::Swift => "Swift",
+                Self::Scala => "Scala",
+                Self::Shell => "Shell",
+                Self::Lua => "Lua",
+                Self::Haskell => "Haskell",
+                Self::LiterateHaskell => "Literate Haskell",
+                Self::Sql => "SQL",
+                Self::Yaml => "YAML",
+                Self::Toml => "TOML",
+                Self::Html => "HTML",
+                Self::Xml => "XML",
+                Self::Css => "CSS",
+                Self::Scss => "SCSS",
+                Self::Less => "LESS",
+                Self::Markdown => "Markdown",
+                Self::Dockerfile => "Dockerfile",
+                Self::Makefile => "Makefile",
+                Self::Justfile => "Justfile",
+                Self::Perl => "Perl",
+                Self::Elixir => "Elixir",
+                Self::Erlang => "Erlang",
+                Self::Dart => "Dart",
+                Self::Vue => "Vue",
+                Self::Svelte => "Svelte",
+                Self::R => "R",
+                Self::Notebook => "Jupyter notebook",
+                Self::PowerShell => "PowerShell",
+                Self::Zig => "Zig",
+                Self::Clojure => "Clojure",
+                Self::EmacsLisp => "Emacs Lisp",
+                Self::CommonLisp => "Common Lisp",
+                Self::VimScript => "Vim script",
+                Self::Latex => "LaTeX",
+                Self::Fortran => "Fortran",
+                Self::Groovy => "Groovy",
+                Self::Custom(_) => "Other",
+            }
+        }
+
+        fn comment_styles(&self) -> &'static [CommentStyle] {
+            match self {
+                Self::Python
+                | Self::Ruby
+                | Self::Shell
+                | Self::Yaml
+                | Self::Toml
+                | Self::Dockerfile
+                | Self::Makefile
+                | Self::Justfile
+                | Self::Perl
+                | Self::Elixir
+                | Self::R
+                | Self::Notebook => &[CommentStyle::Hash],
+                Self::Php => &[
+                    CommentStyle::Slashes,
+                    CommentStyle::Hash,
+                    CommentStyle::Html,
+                    CommentStyle::DocTag,
+                ],
+                Self::Java | Self::TypeScript | Self::JavaScript => {
+                    &[CommentStyle::Slashes, CommentStyle::DocTag]
+                }
+                Self::Erlang | Self::Latex => &[CommentStyle::Percent],
+                Self::Clojure | Self::EmacsLisp | Self::CommonLisp => &[CommentStyle::Semicolon],
+                Self::VimScript => &[CommentStyle::Quote],
+                Self::Fortran => &[CommentStyle::Bang],
+                Self::PowerShell => &[CommentStyle::Hash, CommentStyle::HashBlock],
+                Self::Html | Self::Xml => &[CommentStyle::Html],
+                Self::Markdown => &[CommentStyle::Html, CommentStyle::TaskList],
+                Self::Vue | Self::Svelte => &[CommentStyle::Html, CommentStyle::Slashes],
+                Self::Lua | Self::Haskell | Self::LiterateHaskell | Self::Sql => {
+                    &[CommentStyle::Dashes]
+                }
+                Self::Rust
+                | Self::Go
+                | Self::C
+                | Self::Cpp
+                | Self::CSharp
+                | Self::Kotlin
+                | Self::Swift
+                | Self::Scala
+                | Self::Css
+                | Self::Scss
+                | Self::Less
+                | Self::Dart
+                | Self::Zig
+                | Self::Groovy => &[CommentStyle::Slashes],
+                Self::Custom(syntax) => syntax.comment_styles(),
+            }
+        }
+
+        /// Whether `/* */` comments can span lines, and if so whether they nest.
+        fn block_comments(&self) -> Option<bool> {
+            match self {
+                Self::Rust | Self::Kotlin | Self::Swift | Self::Scala | Self::Dart => Some(true),
+                Self::Java
+                | Self::TypeScript
+                | Self::JavaScript
+                | Self::Go
+                | Self::C
+                | Self::Cpp
+                | Self::Php
+                | Self::CSharp
+                | Self::Css
+                | Self::Scss
+                | Self::Less
+                | Self::Vue
+                | Self::Svelte
+                | Self::Groovy => Some(false),
+                _ => None,
+            }
+        }
+
+        /// How string literals are delimited, so that comment markers inside them are skipped.
+        fn quoting(&self) -> Quoting {
+            match self {
+                Self::Rust => Quoting::Rust,
+                Self::Shell | Self::PowerShell | Self::Dockerfile | Self::Makefile => {
+                    Quoting::Shell
+                }
+                _ => Quoting::Standard,
+            }
+        }
+
+        /// Whether triple-quoted docstrings can hold TODOs.
+        fn docstrings(&self) -> bool {
+            matches!(self, Self::Python)
+        }
+
+        /// Start and end markers of blocks, such as embedded documentation or code fences, whose
+        /// contents are not scanned.
+        fn skipped_blocks(&self) -> &'static [(&'static str, &'static str)] {
+            match self {
+                Self::Ruby => &[("=begin", "=end")],
+                Self::Markdown => &[("```", "```"), ("~~~", "~~~")],
+                _ => &[],
+            }
+        }
+
+        /// Marker that starts code lines in literate sources; all other lines are prose.
+        fn literate_code_marker(&self) -> Option<&'static str> {
+            match self {
+                Self::LiterateHaskell => Some(">"),
+                _ => None,
+            }
+        }
+    }
+
+    /// The `/* */` nesting depth after `line`, given the depth before it. Outside comments, `//`
+    /// comments and `"` strings are skipped.
+    fn block_depth(line: &str, mut depth: usize, nested: bool) -> usize {
+        let bytes = line.as_bytes();
+        let mut in_string = false;
+        let mut i = 0;
+        while i < bytes.len() {
+            let pair = &bytes[i..bytes.len().min(i + 2)];
+            if depth > 0 {
+                if pair == b"*/" {
+                    depth -= 1;
+                    i += 2;
+                    continue;
+                }
+                if nested && pair == b"/*" {
+                    depth += 1;
+                    i += 2;
+                    continue;
+                }
+            } else if in_string {
+                match bytes[i] {
+                    b'\\' => i += 1,
+                    b'"' => in_string = false,
+                    _ => {}
+                }
+            } else if pair == b"//" {
+                break;
+            } else if pair == b"/*" {
+                depth = 1;
+                i += 2;
+                continue;
+            } else if bytes[i] == b'"' {
+                in_string = true;
+            }
+            i += 1;
+        }
+        depth
+    }
+
+    /// The quotes of the Python docstring still open after `line`, given those open before it.
+    /// Outside docstrings, `#` comments and short strings are skipped.
+    fn docstring_quotes(line: &str, mut open: Option<&'static str>) -> Option<&'static str> {
+        let mut rest = line;
+        loop {
+            if let Some(quotes) = open {
+                let Some(end) = rest.find(quotes) else {
+                    return open;
+                };
+                rest = &rest[end + quotes.len()..];
+                open = None;
+                continue;
+            }
+            let start = rest.find(['#', '"', '\''])?;
+            rest = &rest[start..];
+            if rest.starts_with('#') {
+                return None;
+            }
+            if let Some(quotes) = ["\"\"\"", "'''"].into_iter().find(|q| rest.starts_with(q)) {
+                open = Some(quotes);
+                rest = &rest[quotes.len()..];
+                continue;
+            }
+            let quote = rest.as_bytes()[0];
+            let mut escaped = false;
+            let end = rest[1..].bytes().position(|byte| {
+                let end = !escaped && byte == quote;
+                escaped = !escaped && byte == b'\\';
+                end
+            })?;
+            rest = &rest[end + 2..];
+        }
+    }
+
+    /// Whether `line` starts with the quotes of a docstring, as in `"""TODO: text"""`.
+    fn starts_docstring(line: &str) -> bool {
+        let line = line.trim_start();
+        let line = line.strip_prefix(['r', 'R', 'u', 'U']).unwrap_or(line);
+        line.starts_with("\"\"\"") || line.starts_with("'''")
+    }
+
+    /// A TODO comment parsed from a single line.
+    #[derive(Debug, Clone, PartialEq, Eq, Default, Serialize)]
+    pub struct Todo {
+        /// The 1-based column of the keyword, counted in characters, so a tab counts as one.
+        pub column: usize,
+        /// The keyword that matched, such as `TODO` or `FIXME`.
+        pub tag: String,
+        /// The owner from the `TODO(alice): text` convention.
+        pub author: Option<String>,
+        /// The first issue reference in the comment, such as `#4521` or `PROJ-1234`.
+        pub issue: Option<String>,
+        /// The number of `!` after the keyword in `TODO!!: text`, or the `P1` in `TODO(P1): text`.
+        pub priority: Option<String>,
+        /// The date from `TODO(2025-03-01): text` or a `by 2025-03-01` phrase in the comment.
+        pub due: Option<NaiveDate>,
+        /// Whether `due` is before the scanner's reference date, if one was configured.
+        pub overdue: Option<bool>,
+        /// Whether the TODO is in a `///` or `//!` doc comment.
+        pub doc: bool,
+        /// The comment text following the keyword.
+        pub comment: String,
+    }
+
+    /// A [`Todo`] together with where it was found, as passed to a [`TodoWriter`]. The field names
+    /// are the keys of the JSON output.
+    #[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+    pub struct TodoEntry {
+        /// The path of the scanned file.
+        pub file: String,
+        /// The 1-based line number, counted within the cell for notebooks.
+        pub line: usize,
+        /// The 1-based code cell number, for Jupyter notebooks only.
+        pub cell: Option<usize>,
+        /// The language of the file, such as `Rust`, if it is a supported one.
+        pub language: Option<&'static str>,
+        /// The 0-based byte offset of the keyword in the file, for files read line by line.
+        pub offset: Option<usize>,
+        #[serde(flatten)]
+        pub todo: Todo,
+        /// The line the TODO starts on, as read, when the scanner was asked for it.
+        pub raw_line: Option<String>,
+        /// The author of the commit that last changed the line, with `git_blame`.
+        pub blame_author: Option<String>,
+        /// The author date of that commit.
+        pub author_date: Option<NaiveDate>,
+        /// The abbreviated hash of that commit.
+        pub commit: Option<String>,
+        /// The days from `author_date` to the scanner's age reference date.
+        pub age_days: Option<i64>,
+        /// When the file was last modified, to the second, if the platform records it.
+        pub modified: Option<DateTime<Utc>>,
+        /// The lines around the TODO, its own included, when the scanner was asked for context.
+        pub context: Option<Vec<String>>,
+        /// The TODO's URL, rendered from the scanner's [`LinkTemplate`].
+        pub link: Option<String>,
+    }
+
+    impl TodoEntry {
+        /// The Line column: the line number, or `cell N, line M` for notebooks.
+        pub fn location(&self) -> String {
+            match self.cell {
+                Some(cell) => format!("cell {}, line {}", cell, self.line),
+                None => self.line.to_string(),
+            }
+        }
+    }
+
+    /// Passes on the entries of one file, filling in their git blame and age if `blame_path` is
+    /// set and their `context` with the `radius` lines around them. Each entry is held back until
+    /// the lines after it have been pushed.
+    struct FileWriter<'a, W: ?Sized> {
+        inner: &'a mut W,
+        /// The file to blame, until its first entry is written.
+        blame_path: Option<&'a Path>,
+        blame: HashMap<usize, Blame>,
+        age_reference: Option<NaiveDate>,
+        min_age_days: Option<i64>,
+        modified: Option<DateTime<Utc>>,
+        language: Option<FileExtension>,
+        radius: usize,
+        /// The lines pushed so far that an entry may still need, with their 1-based numbers.
+        recent: VecDeque<(usize, String)>,
+        waiting: Vec<TodoEntry>,
+    }
+
+    impl<'a, W: TodoWriter + ?Sized> FileWriter<'a, W> {
+        fn new(
+            inner: &'a mut W,
+            scanner: &Scanner,
+            path: &'a Path,
+            language: Option<FileExtension>,
+        ) -> Self {
+            let blames = scanner.git_blame || scanner.age_reference.is_some();
+            Self {
+                inner,
+                blame_path: blames.then_some(path),
+                blame: HashMap::new(),
+                age_reference: scanner.age_reference,
+                min_age_days: scanner.min_age_days,
+                modified: modified(path),
+                language,
+                radius: scanner.context,
+                recent: VecDeque::new(),
+                waiting: Vec::new(),
+            }
+        }
+
+        /// Records the next line, passing on the entries whose context it completes. Lines are
+        /// kept while an entry from line `oldest` onwards may still be written.
+        fn push_line(&mut self, number: usize, line: &str, oldest: usize) -> io::Result<()> {
+            if self.radius == 0 {
+                return Ok(());
+            }
+            self.recent.push_back((number, line.to_string()));
+            self.release(false)?;
+            let oldest = self
+                .waiting
+                .iter()
+                .map(|entry| entry.line)
+                .fold(oldest, usize::min);
+            while self
+                .recent
+                .front()
+                .is_some_and(|(number, _)| number + self.radius < oldest)
+            {
+                self.recent.pop_front();
+            }
+            Ok(())
+        }
+
+        /// Passes on the waiting entries whose context is complete, or all of them at the end
+        /// of the file.
+        fn release(&mut self, at_end: bool) -> io::Result<()> {
+            let last = self.recent.back().map_or(0, |(number, _)| *number);
+            let (ready, waiting): (Vec<_>, Vec<_>) = std::mem::take(&mut self.waiting)
+                .into_iter()
+                .partition(|entry| at_end || entry.line + self.radius <= last);
+            self.waiting = waiting;
+            for mut entry in ready {
+                let lines = self.recent.iter().filter(|(number, _)| {
+                    number + self.radius >= entry.line && *number <= entry.line + self.radius
+                });
+                entry.context = Some(lines.map(|(_, line)| line.clone()).collect());
+                self.inner.write_entry(&entry)?;
+            }
+            Ok(())
+        }
+    }
+
+    impl<W: TodoWriter + ?Sized> TodoWriter for FileWriter<'_, W> {
+        fn write_entry(&mut self, entry: &TodoEntry) -> io::Result<()> {
+            let mut entry = entry.clone();
+            entry.modified = self.modified;
+            entry.language = self.language.map(|language| language.name());
+            // Files without TODOs are never blamed.
+            if let Some(path) = self.blame_path.take() {
+                self.blame = git::blame(path);
+            }
+            if let Some(blame) = self.blame.get(&entry.line) {
+                entry.blame_author = Some(blame.author.clone());
+                entry.author_date = Some(blame.date);
+                entry.commit = Some(blame.commit.clone());
+            }
+            entry.age_days = self
+                .age_reference
+                .zip(entry.author_date)
+                .map(|(reference, date)| (reference - date).num_days());
+            let too_young = |min: i64| entry.age_days.is_none_or(|age| age < min);
+            if self.min_age_days.is_some_and(too_young) {
+                return Ok(());
+            }
+            if self.radius == 0 {
+                return self.inner.write_entry(&entry);
+            }
+            self.waiting.push(entry);
+            self.release(false)
+        }
+    }
+
+    /// Passes entries on, counting them.
+    struct CountingWriter<'a, W: ?Sized> {
+        inner: &'a mut W,
+        count: usize,
+    }
+
+    impl<W: TodoWriter + ?Sized> TodoWriter for CountingWriter<'_, W> {
+        fn write_entry(&mut self, entry: &TodoEntry) -> io::Result<()> {
+            self.count += 1;
+            self.inner.write_entry(entry)
+        }
+    }
+
+    /// What a [`Scanner::scan_files`] run found. The field names are the keys of its JSON form.
+    #[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+    pub struct ScanStats {
+        pub files_scanned: usize,
+        pub files_with_todos: usize,
+        /// The TODOs found per language name, with `Other` for files in no supported language.
+        /// Languages whose files had no TODOs are included with 0.
+        pub todos_by_language: BTreeMap<&'static str, usize>,
+        #[serde(rename = "elapsed_seconds", serialize_with = "serialize_seconds")]
+        pub elapsed: Duration,
+    }
+
+    impl ScanStats {
+        /// The TODOs found in all languages.
+        pub fn todos(&self) -> usize {
+            self.todos_by_language.values().sum()
+        }
+    }
+
+    fn serialize_seconds<S: Serializer>(
+        duration: &Duration,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.serialize_f64(duration.as_secs_f64())
+    }
+
+    /// The lines of `reader` without their `\n` or `\r\n` endings, each with the byte offset
+    /// it starts at.
+    fn lines_with_offsets<R: BufRead>(
+        mut reader: R,
+    ) -> impl Iterator<Item = io::Result<(usize, String)>> {
+        let mut offset = 0;
+        std::iter::from_fn(move || {
+            let mut line = String::new();
+            match reader.read_line(&mut line) {
+                Ok(0) => None,
+                Ok(read) => {
+                    let start = offset;
+                    offset += read;
+                    if line.ends_with('\n') {
+                        line.pop();
+                        if line.ends_with('\r') {
+                            line.pop();
+                        }
+                    }
+                    Some(Ok((start, line)))
+                }
+                Err(err) => Some(Err(err)),
+            }
+        })
+    }
+
+    /// The modification time of `path`, or `None` where the platform doesn't record it.
+    fn modified(path: &Path) -> Option<DateTime<Utc>> {
+        let modified = DateTime::<Utc>::from(path.metadata().ok()?.modified().ok()?);
+        modified.with_nanosecond(0)
+    }
+
+    /// Returns the comment text captured by `todo_pattern`.
+    pub fn extract_todo_comment(line: &str, todo_pattern: &Regex) -> Option<String> {
+        extract_todo(line, todo_pattern).map(|todo| todo.comment)
+    }
+
+    /// Parses the TODO on `line` from the `tag`, `bangs`, `author` and `text` groups of
+    /// `todo_pattern`. Patterns without a `text` group use their first group as the comment and
+    /// report `TODO`.
+    pub fn extract_todo(line: &str, todo_pattern: &Regex) -> Option<Todo> {
+        todo_from_captures(line, &todo_pattern.captures(line)?)
+    }
+
+    /// Like [`extract_todo`], but also finds comments after code, as with `todo_pattern` built by
+    /// [`ScannerBuilder::include_inline`]. Matches inside string literals, as delimited by
+    /// `quoting`, are skipped.
+    pub fn extract_inline_todo(line: &str, todo_pattern: &Regex, quoting: Quoting) -> Option<Todo> {
+        let mut start = 0;
+        while let Some(captures) = todo_pattern.captures_at(line, start) {
+            let found = captures.get(0)?;
+            if !in_string(&line[..found.start()], quoting) {
+                return todo_from_captures(line, &captures);
+            }
+            start = found.start() + line[found.start()..].chars().next()?.len_utf8();
+        }
+        None
+    }
+
+    /// How a language delimits string literals.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum Quoting {
+        /// `"` and `'` strings with backslash escapes.
+        Standard,
+        /// `"` strings with backslash escapes and `r"..."` or `r#"..."#` raw strings. `'` starts
+        /// char literals such as `'"'`, or lifetimes.
+        Rust,
+        /// `"` strings with backslash escapes and `'` strings without, as in shell scripts.
+        Shell,
+    }
+
+    /// Whether a string literal is still open at the end of `code`.
+    fn in_string(code: &str, quoting: Quoting) -> bool {
+        let mut rest = code;
+        let mut previous = None;
+        while let Some(c) = rest.chars().next() {
+            let after = &rest[c.len_utf8()..];
+            // `None` if `c` doesn't start a literal, `Some(None)` if the literal is unterminated.
+            let literal = match (quoting, c) {
+                (_, '"') => Some(string_end(after, '"', true)),
+                (Quoting::Standard, '\'') => Some(string_end(after, '\'', true)),
+                (Quoting::Shell, '\'') => Some(string_end(after, '\'', false)),
+                (Quoting::Rust, '\'') => char_literal_end(after).map(Some),
+                (Quoting::Rust, 'r')
+                    if !previous.is_some_and(|p: char| p.is_alphanumeric() || p == '_') =>
+                {
+                    raw_string_end(after)
+                }
+                _ => None,
+            };
+            rest = match literal {
+                Some(Some(end)) => end,
+                Some(None) => return true,
+                None => after,
+            };
+            previous = Some(c);
+        }
+        false
+    }
+
+    /// The code after the string whose contents start `code`, or `None` if it isn't closed.
+    fn string_end(code: &str, quote: char, escapes: bool) -> Option<&str> {
+        let mut chars = code.char_indices();
+        while let Some((i, c)) = chars.next() {
+            if escapes && c == '\\' {
+                chars.next();
+            } else if c == quote {
+                return Some(&code[i + c.len_utf8()..]);
+            }
+        }
+        None
+    }
+
+    /// The code after the Rust char literal whose contents start `code`, or `None` if the `'`
+    /// started a lifetime instead.
+    fn char_literal_end(code: &str) -> Option<&str> {
+        let mut chars = code.char_indices();
+        let (_, first) = chars.next()?;
+        if first == '\\' {
+            let end = code.find('\'')?;
+            return Some(&code[end + 1..]);
+        }
+        let (i, c) = chars.next()?;
+        (c == '\'').then(|| &code[i + 1..])
+    }
+
+    /// Like `string_end` for the raw string whose `#`s and `"` follow `code`'s `r`, or `None` if
+    /// the `r` doesn't start one.
+    fn raw_string_end(code: &str) -> Option<Option<&str>> {
+        let hashes = code.len() - code.trim_start_matches('#').len();
+        let contents = code[hashes..].strip_prefix('"')?;
+        let closing = format!("\"{}", "#".repeat(hashes));
+        Some(
+            contents
+                .find(&closing)
+                .map(|end| &contents[end + closing.len()..]),
+        )
+    }
+
+    fn todo_from_captures(line: &str, captures: &regex::Captures) -> Option<Todo> {
+        let comment = captures.name("text").or_else(|| captures.get(1))?.as_str();
+        // A `/* */` comment ends at its first `*/`, whatever code follows it.
+        let mut comment = match captures.name("block") {
+            Some(_) => comment.split("*/").next().unwrap_or_default().trim_end(),
+            None => comment,
+        }
+        .to_string();
+        let mut author = captures
+            .name("author")
+            .map(|m| m.as_str().trim().to_string())
+            .filter(|author| !author.is_empty());
+        let mut priority = captures
+            .name("bangs")
+            .map(|m| m.as_str().len())
+            .filter(|&bangs| bangs > 0)
+            .map(|bangs| bangs.to_string());
+        let mut due = None;
+        if let Some(note) = author.take() {
+            if PRIORITY_PATTERN.is_match(&note) {
+                priority = Some(note);
+            } else if DATE_PATTERN.is_match(&note) {
+                match parse_date(&note) {
+                    Some(date) => due = Some(date),
+                    None => comment = format!("({}) {}", note, comment),
+                }
+            } else {
+                author = Some(note);
+            }
+        }
+        if due.is_none() {
+            due = DUE_PHRASE_PATTERN
+                .captures(&comment)
+                .and_then(|captures| parse_date(&captures[1]));
+        }
+        let start = captures.name("tag").or_else(|| captures.get(0))?.start();
+        Some(Todo {
+            column: line[..start].chars().count() + 1,
+            tag: captures
+                .name("tag")
+                .map_or("TODO", |m| m.as_str())
+                .to_string(),
+            author,
+            issue: None,
+            priority,
+            due,
+            overdue: None,
+            doc: captures.name("doc").is_some(),
+            comment,
+        })
+    }
+
+    fn parse_date(date: &str) -> Option<NaiveDate> {
+        NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()
+    }
+
+    pub fn is_supported_file(entry: &ignore::DirEntry) -> bool {
+        entry.path().is_file() && FileExtension::from_path(entry.path()).is_some()
+    }
+
+    /// The extensions that are scanned: the built-in ones of [`FileExtension::from_path`], plus
+    /// any added at runtime, optionally narrowed to a chosen few.
+    #[derive(Debug, Clone, Default)]
+    pub struct Extensions {
+        added: HashMap<String, CommentSyntax>,
+        only: Option<Vec<String>>,
+    }
+
+    impl Extensions {
+        /// Scans files with the extension `ext`, such as `proto`, for comments in `syntax`. This
+        /// wins over a built-in language with the same extension.
+        pub fn add(&mut self, ext: &str, syntax: CommentSyntax) -> &mut Self {
+            self.added.insert(ext.to_string(), syntax);
+            self
+        }
+
+        /// Scans only the files with one of `exts`, which must be built in or added. Files
+        /// recognized by name, such as `Dockerfile`, are left out too. Fails with the first
+        /// unknown extension.
+        pub fn only<I, S>(&mut self, exts: I) -> Result<&mut Self, String>
+        where
+            I: IntoIterator<Item = S>,
+            S: Into<String>,
+        {
+            let exts: Vec<String> = exts.into_iter().map(Into::into).collect();
+            if let Some(unknown) = exts.iter().find(|ext| {
+                FileExtension::from_str(ext).is_none() && !self.added.contains_key(*ext)
+            }) {
+                return Err(unknown.clone());
+            }
+            self.only = Some(exts);
+            Ok(self)
+        }
+
+        /// The language to scan `path` in, or `None` if it isn't scanned.
+        pub fn language(&self, path: &Path) -> Option<FileExtension> {
+            let ext = path.extension().and_then(|ext| ext.to_str());
+            if let Some(only) = &self.only {
+                if !ext.is_some_and(|ext| only.iter().any(|only| only == ext)) {
+                    return None;
+                }
+            }
+            match ext.and_then(|ext| self.added.get(ext)) {
+                Some(syntax) => Some(FileExtension::Custom(*syntax)),
+                None => FileExtension::from_path(path),
+            }
+        }
+    }
+
+    /// How the File column spells paths.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+    pub enum PathStyle {
+        /// As the directory walk yields them, starting with the scanned directory.
+        #[default]
+        AsGiven,
+        /// Relative to the scanned directory.
+        Relative,
+        /// Absolute, with symlinks resolved.
+        Absolute,
+        /// Relative to the root of the git repository holding the scanned directory.
+        RepoRelative,
+    }
+
+    impl PathStyle {
+        pub fn from_name(name: &str) -> Option<Self> {
+            match name {
+                "as-given" => Some(Self::AsGiven),
+                "relative" => Some(Self::Relative),
+                "absolute" => Some(Self::Absolute),
+                "repo-relative" => Some(Self::RepoRelative),
+                _ => None,
+            }
+        }
+    }
+
+    /// The nearest directory above `dir`, or `dir` itself, with a `.git` entry.
+    fn repository_root(dir: &Path) -> Option<PathBuf> {
+        let dir = dir.canonicalize().ok()?;
+        dir.ancestors()
+            .find(|dir| dir.join(".git").exists())
+            .map(Path::to_path_buf)
+    }
+
+    /// The longest path that all of `paths` start with, compared component by component, such as
+    /// `crates` for `crates/core` and `crates/cli`. Empty if they have nothing in common.
+    pub fn common_root<'a>(paths: impl IntoIterator<Item = &'a Path>) -> PathBuf {
+        let mut paths = paths.into_iter();
+        let Some(first) = paths.next() else {
+            return PathBuf::new();
+        };
+        let mut common: Vec<_> = first.components().collect();
+        for path in paths {
+            let shared = common
+                .iter()
+                .zip(path.components())
+                .take_while(|(a, b)| **a == *b)
+                .count();
+            common.truncate(shared);
+        }
+        common.iter().collect()
+    }
+
+    /// `path` with backslashes turned into forward slashes and without the `\\?\` prefix of
+    /// Windows verbatim paths, so `\\?\C:\src\lib.rs` becomes `C:/src/lib.rs` and
+    /// `\\?\UNC\server\share` becomes `//server/share`.
+    pub fn slash_path(path: &Path) -> String {
+        let path = path.to_str().unwrap_or_default();
+        let path = match path.strip_prefix(r"\\?\") {
+            Some(verbatim) => match verbatim.strip_prefix(r"UNC\") {
+                Some(share) => format!(r"\\{}", share),
+                None => verbatim.to_string(),
+            },
+            None => path.to_string(),
+        };
+        path.replace('\\', "/")
+    }
+
+    /// A piece of a [`LinkTemplate`].
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    enum LinkPart {
+        Text(String),
+        Path,
+        Line,
+    }
+
+    /// A URL with `{path}`, `{line}` and `{commit}` placeholders, such as
+    /// `https://github.com/org/repo/blob/{commit}/{path}#L{line}`, for the Link column.
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct LinkTemplate {
+        parts: Vec<LinkPart>,
+    }
+
+    impl LinkTemplate {
+        /// Parses `template`, filling in `{commit}` with `commit`, a hash or branch name. Fails on
+        /// unknown placeholders, unclosed braces, and on `{commit}` without a `commit`.
+        pub fn parse(template: &str, commit: Option<&str>) -> Result<Self, String> {
+            let mut parts = Vec::new();
+            let mut text = String::new();
+            let mut rest = template;
+            while let Some(open) = rest.find('{') {
+                text.push_str(&rest[..open]);
+                let Some(close) = rest[open..].find('}') else {
+                    return Err(format!("unclosed `{{` in link template \"{}\"", template));
+                };
+                let name = &rest[open + 1..open + close];
+                rest = &rest[open + close + 1..];
+                let part = match name {
+                    "path" => LinkPart::Path,
+                    "line" => LinkPart::Line,
+                    "commit" => match commit {
+                        Some(commit) => {
+                            text.push_str(commit);
+                            continue;
+                        }
+                        None => {
+                            return Err(
+                                "{commit} in the link template needs --git-blame or --link-ref"
+                                    .to_string(),
+                            );
+                        }
+                    },
+                    _ => return Err(format!("unknown placeholder {{{}}} in link template", name)),
+                };
+                if !text.is_empty() {
+                    parts.push(LinkPart::Text(std::mem::take(&mut text)));
+                }
+                parts.push(part);
+            }
+            text.push_str(rest);
+            if !text.is_empty() {
+                parts.push(LinkPart::Text(text));
+            }
+            Ok(Self { parts })
+        }
+
+        /// The URL for `line` of `path`, a `/`-separated path whose segments are percent-encoded.
+        pub fn render(&self, path: &str, line: usize) -> String {
+            let mut url = String::new();
+            for part in &self.parts {
+                match part {
+                    LinkPart::Text(text) => url.push_str(text),
+                    LinkPart::Path => {
+                        let segments: Vec<_> = path.split('/').map(percent_encode).collect();
+                        url.push_str(&segments.join("/"));
+                    }
+                    LinkPart::Line => url.push_str(&line.to_string()),
+                }
+            }
+            url
+        }
+    }
+
+    /// `segment` with every byte but the URL-unreserved ones percent-encoded.
+    fn percent_encode(segment: &str) -> String {
+        let mut encoded = String::new();
+        for byte in segment.bytes() {
+            if byte.is_ascii_alphanumeric() || b"-._~".contains(&byte) {
+                encoded.push(byte as char);
+            } else {
+                encoded.push_str(&format!("%{:02X}", byte));
+            }
+        }
+        encoded
+    }
+
+    /// Configures and builds a [`Scanner`].
+    #[derive(Debug, Clone)]
+    pub struct ScannerBuilder {
+        keywords: Vec<String>,
+        ignore_case: bool,
+        issue_pattern: Option<Regex>,
+        reference_date: Option<NaiveDate>,
+        aliases: HashMap<String, String>,
+        include_inline: bool,
+        multiline: bool,
+        context: usize,
+        raw_line: bool,
+        git_blame: bool,
+        age_reference: Option<NaiveDate>,
+        min_age_days: Option<i64>,
+        path_style: PathStyle,
+        root: PathBuf,
+        other_roots: Vec<PathBuf>,
+        slash_paths: bool,
+        link_template: Option<LinkTemplate>,
+    }
+
+    impl Default for ScannerBuilder {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+
+    impl ScannerBuilder {
+        pub fn new() -> Self {
+            Self {
+                keywords: default_keywords().iter().map(|k| k.to_string()).collect(),
+                ignore_case: false,
+                issue_pattern: Some(ISSUE_PATTERN.clone()),
+                reference_date: None,
+                aliases: HashMap::new(),
+                include_inline: false,
+                multiline: false,
+                context: 0,
+                raw_line: false,
+                git_blame: false,
+                age_reference: None,
+                min_age_days: None,
+                path_style: PathStyle::AsGiven,
+                root: PathBuf::from("."),
+                other_roots: Vec::new(),
+                slash_paths: false,
+                link_template: None,
+            }
+        }
+
+        /// Sets the keywords to scan for, replacing [`default_keywords`]. Keywords are matched
+        /// literally and reported as given in the Tag column.
+        pub fn keywords<I, S>(&mut self, keywords: I) -> &mut Self
+        where
+            I: IntoIterator<Item = S>,
+            S: Into<String>,
+        {
+            self.keywords = keywords.into_iter().map(Into::into).collect();
+            self
+        }
+
+        /// Matches keywords regardless of case. The comment text keeps its casing and the Tag
+        /// column still reports the keyword as configured.
+        pub fn ignore_case(&mut self, yes: bool) -> &mut Self {
+            self.ignore_case = yes;
+            self
+        }
+
+        /// Sets the pattern whose first match in a comment fills the Issue column. Defaults to
+        /// GitHub-style `#123` and Jira-style `PROJ-123` references; `None` disables the column.
+        pub fn issue_pattern(&mut self, pattern: Option<Regex>) -> &mut Self {
+            self.issue_pattern = pattern;
+            self
+        }
+
+        /// Flags entries due before `date` in an extra Overdue column.
+        pub fn reference_date(&mut self, date: Option<NaiveDate>) -> &mut Self {
+            self.reference_date = date;
+            self
+        }
+
+        /// Maps extra keywords to the tag they are reported under, e.g. `REVIEW` to `TODO`. The
+        /// aliases are matched like keywords, and the canonical tag doesn't need to be one.
+        pub fn aliases(&mut self, aliases: HashMap<String, String>) -> &mut Self {
+            self.aliases = aliases;
+            self
+        }
+
+        /// Also reports `//` and `#` comments that follow code on the same line, such as
+        /// `let x = 5; // TODO: use a constant`, unless the marker is inside a string literal.
+        pub fn include_inline(&mut self, yes: bool) -> &mut Self {
+            self.include_inline = yes;
+            self
+        }
+
+        /// Appends the comment lines that follow a TODO, in the same comment style and without a
+        /// keyword of their own, to its comment. The Line column reports the first line.
+        pub fn multiline(&mut self, yes: bool) -> &mut Self {
+            self.multiline = yes;
+            self
+        }
+
+        /// Records the `lines` lines before and after each TODO, with the TODO's own, in an extra
+        /// Context column.
+        pub fn context(&mut self, lines: usize) -> &mut Self {
+            self.context = lines;
+            self
+        }
+
+        /// Records the line each TODO starts on, without a trailing carriage return, in an extra
+        /// RawLine column.
+        pub fn raw_line(&mut self, yes: bool) -> &mut Self {
+            self.raw_line = yes;
+            self
+        }
+
+        /// Runs `git blame` on each file with TODOs and records who last changed their lines, and
+        /// when, in extra BlameAuthor and AuthorDate columns. These are empty for files outside a
+        /// git work tree and for uncommitted lines.
+        pub fn git_blame(&mut self, yes: bool) -> &mut Self {
+            self.git_blame = yes;
+            self
+        }
+
+        /// Reports how many days before `date` each TODO's line was last committed, from the same
+        /// `git blame` as [`git_blame`](Self::git_blame), in an extra AgeDays column.
+        pub fn age_reference(&mut self, date: Option<NaiveDate>) -> &mut Self {
+            self.age_reference = date;
+            self
+        }
+
+        /// Leaves out the TODOs that are younger than `days` or have no git history. Needs an
+        /// [`age_reference`](Self::age_reference).
+        pub fn min_age_days(&mut self, days: Option<i64>) -> &mut Self {
+            self.min_age_days = days;
+            self
+        }
+
+        /// Spells the paths in the File column in `style`, for files found under `root`.
+        pub fn path_style(&mut self, style: PathStyle, root: impl Into<PathBuf>) -> &mut Self {
+            self.path_style = style;
+            self.root = root.into();
+            self
+        }
+
+        /// The directories scanned besides the [`path_style`](Self::path_style) root. Relative
+        /// paths then start from the directory they all share, so that files with the same name
+        /// under different roots stay apart.
+        pub fn other_roots(&mut self, roots: impl IntoIterator<Item = PathBuf>) -> &mut Self {
+            self.other_roots = roots.into_iter().collect();
+            self
+        }
+
+        /// Writes the paths in the File column with forward slashes, as with [`slash_path`], so
+        /// reports from Windows and Unix runs can be compared.
+        pub fn slash_paths(&mut self, yes: bool) -> &mut Self {
+            self.slash_paths = yes;
+            self
+        }
+
+        /// Adds a Link column rendered from `template`, with paths relative to the root of the
+        /// git repository holding the [`path_style`](Self::path_style) root, or to that root
+        /// outside a repository.
+        pub fn link_template(&mut self, template: Option<LinkTemplate>) -> &mut Self {
+            self.link_template = template;
+            self
+        }
+
+        pub fn build(&self) -> Scanner {
+            let mut keywords = self.keywords.clone();
+            keywords.extend(self.aliases.keys().cloned());
+            let compile = |styles: &[CommentStyle], inline: bool| {
+                if keywords.is_empty() {
+                    return HashMap::new();
+                }
+                styles
+                    .iter()
+                    .map(|&style| {
+                        let pattern = todo_pattern(style, &keywords, self.ignore_case, inline);
+                        (style, pattern)
+                    })
+                    .collect()
+            };
+            let patterns = compile(&CommentStyle::ALL, false);
+            let inline_patterns = if self.include_inline {
+                compile(&[CommentStyle::Slashes, CommentStyle::Hash], true)
+            } else {
+                HashMap::new()
+            };
+            let continuation_patterns = if self.multiline {
+                CommentStyle::ALL
+                    .iter()
+                    .map(|&style| (style, continuation_pattern(style)))
+                    .collect()
+            } else {
+                HashMap::new()
+            };
+            let roots = std::iter::once(&self.root)
+                .chain(&self.other_roots)
+                .map(PathBuf::as_path);
+            let rust_macros = keywords.iter().any(|keyword| {
+                keyword == "TODO" || self.ignore_case && keyword.eq_ignore_ascii_case("TODO")
+            });
+            Scanner {
+                keywords,
+                rust_macros,
+                aliases: self.aliases.clone(),
+                patterns,
+                inline_patterns,
+                continuation_patterns,
+                multiline: self.multiline,
+                context: self.context,
+                raw_line: self.raw_line,
+                git_blame: self.git_blame,
+                age_reference: self.age_reference,
+                min_age_days: self.min_age_days,
+                path_style: self.path_style,
+                path_root: match self.path_style {
+                    PathStyle::Relative => Some(common_root(roots)),
+                    PathStyle::RepoRelative => {
+                        let repositories: Vec<_> = roots.filter_map(repository_root).collect();
+                        (!repositories.is_empty())
+                            .then(|| common_root(repositories.iter().map(PathBuf::as_path)))
+                    }
+                    PathStyle::AsGiven | PathStyle::Absolute => None,
+                },
+                slash_paths: self.slash_paths,
+                link: self.link_template.clone().map(|template| {
+                    let root = repository_root(&self.root)
+                        .or_else(|| self.root.canonicalize().ok())
+                        .unwrap_or_else(|| self.root.clone());
+                    (template, root)
+                }),
+                issue_pattern: self.issue_pattern.clone(),
+                reference_date: self.reference_date,
+            }
+        }
+    }
+
+    /// Extracts TODO comments from files using compiled per-language patterns.
+    #[derive(Debug, Clone)]
+    pub struct Scanner {
+        keywords: Vec<String>,
+        /// Whether `todo!()` and `unimplemented!()` are reported, which they are when scanning
+        /// for `TODO`.
+        rust_macros: bool,
+        aliases: HashMap<String, String>,
+        patterns: HashMap<CommentStyle, Regex>,
+        inline_patterns: HashMap<CommentStyle, Regex>,
+        continuation_patterns: HashMap<CommentStyle, Regex>,
+        multiline: bool,
+        context: usize,
+        raw_line: bool,
+        git_blame: bool,
+        age_reference: Option<NaiveDate>,
+        min_age_days: Option<i64>,
+        path_style: PathStyle,
+        /// The directory that relative paths start from.
+        path_root: Option<PathBuf>,
+        slash_paths: bool,
+        /// The link template and the directory its paths are relative to.
+        link: Option<(LinkTemplate, PathBuf)>,
+        issue_pattern: Option<Regex>,
+        reference_date: Option<NaiveDate>,
+    }
+
+    impl Default for Scanner {
+        fn default() -> Self {
+            ScannerBuilder::new().build()
+        }
+    }
+
+    impl Scanner {
+        /// The columns written for this scanner: [`Column::DEFAULT`], plus `Overdue` when a
+        /// reference date is configured.
+        pub fn columns(&self) -> Vec<Column> {
+            let mut columns = Column::DEFAULT.to_vec();
+            if self.reference_date.is_some() {
+                columns.insert(columns.len() - 1, Column::Overdue);
+            }
+            if self.git_blame {
+                let comment = columns.len() - 1;
+                columns.splice(
+                    comment..comment,
+                    [Column::BlameAuthor, Column::AuthorDate, Column::Commit],
+                );
+            }
+            if self.age_reference.is_some() {
+                columns.insert(columns.len() - 1, Column::AgeDays);
+            }
+            if self.link.is_some() {
+                columns.push(Column::Link);
+            }
+            if self.raw_line {
+                columns.push(Column::RawLine);
+            }
+            if self.context > 0 {
+                columns.push(Column::Context);
+            }
+            columns
+        }
+
+        fn extract(&self, line: &str, style: CommentStyle) -> Option<Todo> {
+            let todo = extract_todo(line, self.patterns.get(&style)?)?;
+            Some(self.canonicalize(todo))
+        }
+
+        fn extract_inline(
+            &self,
+            line: &str,
+            style: CommentStyle,
+            quoting: Quoting,
+        ) -> Option<Todo> {
+            let pattern = self.inline_patterns.get(&style)?;
+            let todo = extract_inline_todo(line, pattern, quoting)?;
+            Some(self.canonicalize(todo))
+        }
+
+        /// Reports the tag as configured and fills in the fields that depend on the scanner.
+        fn canonicalize(&self, mut todo: Todo) -> Todo {
+            if let Some(keyword) = self
+                .keywords
+                .iter()
+                .find(|keyword| keyword.to_lowercase() == todo.tag.to_lowercase())
+            {
+                todo.tag = self.aliases.get(keyword).unwrap_or(keyword).clone();
+            }
+            todo.issue = self.find_issue(&todo.comment);
+            todo.overdue = self
+                .reference_date
+                .zip(todo.due)
+                .map(|(reference, due)| due < reference);
+            todo
+        }
+
+        fn entry(
+            &self,
+            path: &Path,
+            line: usize,
+            cell: Option<usize>,
+            line_offset: Option<usize>,
+            raw_line: &str,
+            todo: Todo,
+        ) -> TodoEntry {
+            let keyword = raw_line
+                .char_indices()
+                .nth(todo.column - 1)
+                .map_or(raw_line.len(), |(start, _)| start);
+            TodoEntry {
+                file: self.display_path(path),
+                line,
+                cell,
+                language: None,
+                offset: line_offset.map(|line_offset| line_offset + keyword),
+                todo,
+                raw_line: self
+                    .raw_line
+                    .then(|| raw_line.trim_end_matches('\r').to_string()),
+                blame_author: None,
+                author_date: None,
+                commit: None,
+                age_days: None,
+                modified: None,
+                context: None,
+                link: self.link(path, line),
+            }
+        }
+
+        /// The Link column for `line` of `path`, if a link template is configured and `path` is
+        /// under its root.
+        fn link(&self, path: &Path, line: usize) -> Option<String> {
+            let (template, root) = self.link.as_ref()?;
+            let absolute = path.canonicalize().ok()?;
+            let relative = absolute.strip_prefix(root).ok()?;
+            let segments: Vec<_> = relative
+                .components()
+                .map(|component| component.as_os_str().to_str())
+                .collect::<Option<_>>()?;
+            Some(template.render(&segments.join("/"), line))
+        }
+
+        /// The File column for `path`, spelled in the configured [`PathStyle`]. Paths that can't
+        /// be resolved, or aren't under any root, are kept as given.
+        fn display_path(&self, path: &Path) -> String {
+            let absolute = || path.canonicalize().ok();
+            let display = match (self.path_style, &self.path_root) {
+                (PathStyle::Relative, Some(root)) => path
+                    .strip_prefix(root)
+                    .ok()
+                    .filter(|relative| !relative.as_os_str().is_empty())
+                    .map(Path::to_path_buf),
+                (PathStyle::RepoRelative, Some(root)) => absolute()
+                    .and_then(|absolute| Some(absolute.strip_prefix(root).ok()?.to_path_buf())),
+                (PathStyle::Absolute, _) => absolute(),
+                _ => None,
+            };
+            let display = display.as_deref().unwrap_or(path);
+            if self.slash_paths {
+                slash_path(display)
+            } else {
+                display.to_str().unwrap_or_default().to_string()
+            }
+        }
+
+        fn find_issue(&self, comment: &str) -> Option<String> {
+            let issue = self.issue_pattern.as_ref()?.find(comment)?;
+            Some(issue.as_str().to_string())
+        }
+
+        /// Scans each of `files` in its language, as with [`process_file_as`](Self::process_file_as),
+        /// stopping at the first error. Each file is logged with its number of TODOs at the `info`
+        /// level.
+        pub fn scan_files<W, I, P>(&self, files: I, writer: &mut W) -> io::Result<ScanStats>
+        where
+            W: TodoWriter + ?Sized,
+            I: IntoIterator<Item = (P, Option<FileExtension>)>,
+            P: AsRef<Path>,
+        {
+            let started = Instant::now();
+            let mut stats = ScanStats::default();
+            for (path, language) in files {
+                let mut counter = CountingWriter {
+                    inner: writer,
+                    count: 0,
+                };
+                self.process_file_as(path.as_ref(), language, &mut counter)?;
+                stats.files_scanned += 1;
+                if counter.count > 0 {
+                    stats.files_with_todos += 1;
+                }
+                log::info!("{}: {} TODOs", path.as_ref().display(), counter.count);
+                let name = language.map_or("Other", |language| language.name());
+                *stats.todos_by_language.entry(name).or_default() += counter.count;
+            }
+            stats.elapsed = started.elapsed();
+            Ok(stats)
+        }
+
+        /// Scans `path` in the language its name implies, as with [`FileExtension::from_path`].
+        pub fn process_file<W: TodoWriter + ?Sized>(
+            &self,
+            path: &Path,
+            writer: &mut W,
+        ) -> io::Result<()> {
+            self.process_file_as(path, FileExtension::from_path(path), writer)
+        }
+
+        /// Scans `path` as a file in `extension`, already resolved by the caller. Files in no
+        /// supported language are scanned for `//` and `#` comments.
+        pub fn process_file_as<W: TodoWriter + ?Sized>(
+            &self,
+            path: &Path,
+            extension: Option<FileExtension>,
+            writer: &mut W,
+        ) -> io::Result<()> {
+            if extension == Some(FileExtension::Notebook) {
+                return self.process_notebook(path, writer);
+            }
+
+            let file = File::open(path)?;
+            let reader = BufReader::new(file);
+            let comment_styles = extension
+                .map_or(&[CommentStyle::Slashes, CommentStyle::Hash][..], |ext| {
+                    ext.comment_styles()
+                });
+            let skipped_blocks = extension.map_or(&[][..], |ext| ext.skipped_blocks());
+            let code_marker = extension.and_then(|ext| ext.literate_code_marker());
+            let block_comments = extension.and_then(|ext| ext.block_comments());
+            let docstrings = extension.is_some_and(|ext| ext.docstrings());
+            let quoting = extension.map_or(Quoting::Standard, |ext| ext.quoting());
+            let mut skipped_block_end = None;
+            let mut block_depth = 0;
+            let mut docstring = None;
+            // With `multiline`, a TODO is held back until its continuation lines end.
+            let mut pending: Option<(CommentStyle, TodoEntry)> = None;
+            let writer = &mut FileWriter::new(writer, self, path, extension);
+            let flush = |pending: &mut Option<(CommentStyle, TodoEntry)>,
+                         writer: &mut FileWriter<W>|
+             -> io::Result<()> {
+                if let Some((_, mut entry)) = pending.take() {
+                    entry.todo.issue = self.find_issue(&entry.todo.comment);
+                    writer.write_entry(&entry)?;
+                }
+                Ok(())
+            };
+
+            for (line_number, line) in lines_with_offsets(reader).enumerate() {
+                let (line_offset, line) = line?;
+                let oldest = pending
+                    .as_ref()
+                    .map_or(line_number + 1, |(_, entry)| entry.line);
+                writer.push_line(line_number + 1, &line, oldest)?;
+                if let Some(end) = skipped_block_end {
+                    if line.starts_with(end) {
+                        skipped_block_end = None;
+                    }
+                    continue;
+                }
+                if let Some((_, end)) = skipped_blocks
+                    .iter()
+                    .find(|(begin, _)| line.starts_with(begin))
+                {
+                    flush(&mut pending, writer)?;
+                    skipped_block_end = Some(*end);
+                    continue;
+                }
+                let code = match code_marker {
+                    Some(marker) => match line.strip_prefix(marker) {
+                        Some(code) => code,
+                        None => {
+                            flush(&mut pending, writer)?;
+                            continue;
+                        }
+                    },
+                    None => &line,
+                };
+                let offset = line[..line.len() - code.len()].chars().count();
+
+                let continuation = pending.as_ref().and_then(|(style, _)| {
+                    // Inside a block comment, the lines after `/* TODO: text` continue it too.
+                    if block_depth > 0 {
+                        self.continuation(code, CommentStyle::BlockInterior)
+                    } else if *style == CommentStyle::BlockInterior
+                        || (*style == CommentStyle::Docstring && docstring.is_none())
+                    {
+                        None
+                    } else {
+                        self.continuation(code, *style)
+                    }
+                });
+                if let (Some(text), Some((_, entry))) = (continuation, pending.as_mut()) {
+                    entry.todo.comment.push(' ');
+                    entry.todo.comment.push_str(text);
+                } else {
+                    flush(&mut pending, writer)?;
+                    let styles: &[CommentStyle] = if block_depth > 0 {
+                        &[CommentStyle::BlockInterior]
+                    } else if docstrings && (docstring.is_some() || starts_docstring(code)) {
+                        &[CommentStyle::Docstring]
+                    } else {
+                        &[]
+                    };
+                    if let Some((style, mut todo)) = styles
+                        .iter()
+                        .chain(comment_styles)
+                        .find_map(|style| Some((*style, self.extract(code, *style)?)))
+                    {
+                        todo.column += offset;
+                        pending = Some((
+                            style,
+                            self.entry(path, line_number + 1, None, Some(line_offset), &line, todo),
+                        ));
+                        if !self.multiline {
+                            flush(&mut pending, writer)?;
+                        }
+                    } else if block_depth > 0 {
+                        // The rest of the line is comment text without a TODO.
+                    } else if let Some(mut todo) = comment_styles
+                        .iter()
+                        .find_map(|style| self.extract_inline(code, *style, quoting))
+                    {
+                        todo.column += offset;
+                        writer.write_entry(&self.entry(
+                            path,
+                            line_number + 1,
+                            None,
+                            Some(line_offset),
+                            &line,
+                            todo,
+                        ))?;
+                    } else if self.rust_macros
+                        && extension == Some(FileExtension::Rust)
+                        && !code.trim_start().starts_with("//")
+                    {
+                        for captures in RUST_MACRO_PATTERN
+                            .captures_iter(code)
+                            .filter(|c| !in_string(&code[..c.get(0).unwrap().start()], quoting))
+                        {
+                            let comment = captures
+                                .name("text")
+                                .map_or(String::new(), |m| m.as_str().to_string());
+                            let tag = captures.name("tag").unwrap();
+                            let todo = Todo {
+                                column: offset + code[..tag.start()].chars().count() + 1,
+                                tag: tag.as_str().to_string(),
+                                author: None,
+                                issue: self.find_issue(&comment),
+                                priority: None,
+                                due: None,
+                                overdue: None,
+                                doc: false,
+                                comment,
+                            };
+                            writer.write_entry(&self.entry(
+                                path,
+                                line_number + 1,
+                                None,
+                                Some(line_offset),
+                                &line,
+                                todo,
+                            ))?;
+                        }
+                    }
+                }
+                if let Some(nested) = block_comments {
+                    block_depth = self::block_depth(code, block_depth, nested);
+                }
+                if docstrings {
+                    docstring = docstring_quotes(code, docstring);
+                }
+            }
+            flush(&mut pending, writer)?;
+            writer.release(true)
+        }
+
+        /// The text of `code` if it continues a TODO written in `style`: a non-empty comment in
+        /// the same style without a keyword of its own.
+        fn continuation<'a>(&self, code: &'a str, style: CommentStyle) -> Option<&'a str> {
+            if self.extract(code, style).is_some() {
+                return None;
+            }
+            let text = match style {
+                CommentStyle::BlockInterior => {
+                    let inside = code.split("*/").next().unwrap_or_default();
+                    inside.trim().trim_start_matches('*').trim()
+                }
+                _ => {
+                    let captures = self.continuation_patterns.get(&style)?.captures(code)?;
+                    captures.name("text")?.as_str()
+                }
+            };
+            (!text.is_empty()).then_some(text)
+        }
+
+        /// Scans the code cells of a Jupyter notebook, reporting locations as `cell N, line M`.
+        /// Malformed notebooks are skipped with a warning.
+        fn process_notebook<W: TodoWriter + ?Sized>(
+            &self,
+            path: &Path,
+            writer: &mut W,
+        ) -> io::Result<()> {
+            let file = File::open(path)?;
+            let notebook: Value = match serde_json::from_reader(BufReader::new(file)) {
+                Ok(notebook) => notebook,
+                Err(err) => {
+                    log::warn!("skipping malformed notebook {}: {}", path.display(), err);
+                    return Ok(());
+                }
+            };
+            let Some(cells) = notebook.get("cells").and_then(Value::as_array) else {
+                log::warn!("skipping notebook without cells {}", path.display());
+                return Ok(());
+            };
+
+            let writer = &mut FileWriter::new(writer, self, path, Some(FileExtension::Notebook));
+            // Blame is by line of the JSON file, which the cell lines don't map to.
+            writer.blame_path = None;
+            for (cell_number, cell) in cells.iter().enumerate() {
+                if cell.get("cell_type").and_then(Value::as_str) != Some("code") {
+                    continue;
+                }
+                let source = match cell.get("source") {
+                    Some(Value::String(source)) => source.clone(),
+                    Some(Value::Array(lines)) => lines.iter().filter_map(Value::as_str).collect(),
+                    _ => continue,
+                };
+                for (line_number, line) in source.lines().enumerate() {
+                    writer.push_line(line_number + 1, line, line_number + 1)?;
+                    if let Some(todo) = self.extract(line, CommentStyle::Hash) {
+                        let cell = Some(cell_number + 1);
+                        let entry = self.entry(path, line_number + 1, cell, None, line, todo);
+                        writer.write_entry(&entry)?;
+                    }
+                }
+                // The context stays within the cell, whose line numbers start over.
+                writer.release(true)?;
+                writer.recent.clear();
+            }
+
+            writer.release(true)
+        }
+    }
+
+    /// Scans `path` for the [`default_keywords`].
+    pub fn process_file<W: io::Write>(path: &Path, csv_writer: &mut Writer<W>) -> io::Result<()> {
+        DEFAULT_SCANNER.process_file(path, csv_writer)
+    }
+
+    /// Scans `path` for `keywords`. Build a [`Scanner`] instead when scanning many files, so the
+    /// patterns are only compiled once.
+    pub fn process_file_with_keywords<W: io::Write>(
+        path: &Path,
+        csv_writer: &mut Writer<W>,
+        keywords: &[&str],
+    ) -> io::Result<()> {
+        ScannerBuilder::new()
+            .keywords(keywords.iter().copied())
+            .build()
+            .process_file(path, csv_writer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        config::Config,
+        git,
+        output::{self, TodoWriter},
+        utils,
+    };
+    use csv::{ReaderBuilder, StringRecord, Writer};
+    use ignore::WalkBuilder;
+    use lazy_static::lazy_static;
+    use regex::Regex;
+    use std::{collections::HashMap, fs, path::Path};
+
+    lazy_static! {
+        static ref TODO_PATTERN: Regex =
+            Regex::new(r"(?m)^(?:\s*//|\s*#)\s*TODO:\s*(.*\S)\s*$").unwrap();
+    }
+
+    fn process_files(paths: &[&Path], out_dir: &Path) -> Vec<StringRecord> {
+        let output = out_dir.join("out.csv");
+        let mut csv_writer = Writer::from_path(&output).unwrap();
+        for path in paths {
+            utils::process_file(path, &mut csv_writer).unwrap();
+        }
+        csv_writer.flush().unwrap();
+
+        ReaderBuilder::new()
+            .has_headers(false)
+            .from_path(&output)
+            .unwrap()
+            .records()
+            .map(Result::unwrap)
+            .collect()
+    }
+
+    /// Sets the modification time of `path` to `seconds` after the Unix epoch.
+    fn set_modified(path: &Path, seconds: u64) {
+        let time = std::time::UNIX_EPOCH + std::time::Duration::from_secs(seconds);
+        let file = fs::File::options().write(true).open(path).unwrap();
+        file.set_modified(time).unwrap();
+    }
+
+    fn scan_fixture(file_name: &str, contents: &str) -> Vec<StringRecord> {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(file_name);
+        fs::write(&path, contents).unwrap();
+        process_files(&[&path], dir.path())
+    }
+
+    #[test]
+    fn test_extract_todo_comment() {
+        let line_with_todo = "    // TODO: Implement the new feature";
+        let line_without_todo = "    // This is a regular comment";
+        let line_with_no_comment = "let x = 5;";
+
+        assert_eq!(
+            utils::extract_todo_comment(line_with_todo, &TODO_PATTERN),
+            Some(String::from("Implement the new feature"))
+        );
+        assert_eq!(
+            utils::extract_todo_comment(line_without_todo, &TODO_PATTERN),
+            None
+        );
+        assert_eq!(
+            utils::extract_todo_comment(line_with_no_comment, &TODO_PATTERN),
+            None
+        );
+    }
+
+    #[test]
+    fn test_extract_todo_comment_with_whitespace() {
+        let line_with_todo = "    //   TODO:  Improve error handling  ";
+        let expected = Some(String::from("Improve error handling"));
+
+        assert_eq!(
+            utils::extract_todo_comment(line_with_todo, &TODO_PATTERN),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_extract_todo_comment_multiline() {
+        let line_with_multiline_todo =
+            "    // TODO: Refactor this code\n    // to make it more efficient";
+        let expected = Some(String::from("Refactor this code"));
+
+        assert_eq!(
+            utils::extract_todo_comment(line_with_multiline_todo, &TODO_PATTERN),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_extract_todo_comment_inline() {
+        let line_with_inline_todo =
+            "let x = 5; // TODO: Use a constant instead of a hardcoded value";
+        let expected = None;
+
+        assert_eq!(
+            utils::extract_todo_comment(line_with_inline_todo, &TODO_PATTERN),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_extract_todo_comment_python() {
+        let line_with_todo = "# TODO: Implement the new feature";
+        let line_without_todo = "# This is a regular comment";
+        let line_with_no_comment = "x = 5";
+
+        assert_eq!(
+            utils::extract_todo_comment(line_with_todo, &TODO_PATTERN),
+            Some(String::from("Implement the new feature"))
+        );
+        assert_eq!(
+            utils::extract_todo_comment(line_without_todo, &TODO_PATTERN),
+            None
+        );
+        assert_eq!(
+            utils::extract_todo_comment(line_with_no_comment, &TODO_PATTERN),
+            None
+        );
+    }
+
+    #[test]
+    fn test_extract_todo_comment_python_inline() {
+        let line_with_inline_todo = "x = 5  # TODO: Use a constant instead of a hardcoded value";
+        let expected = None;
+
+        assert_eq!(
+            utils::extract_todo_comment(line_with_inline_todo, &TODO_PATTERN),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_process_file_go() {
+        let records = scan_fixture(
+            "main.go",
+            "package main\n\n// TODO: Handle the error\nfunc main() {\n\t// TODO: Add flags\n}\n",
+        );
+
+        assert_eq!(records.len(), 2);
+        assert!(records[0][0].ends_with("main.go"));
+        assert_eq!(&records[0][1], "3");
+        assert_eq!(&records[0][7], "Handle the error");
+        assert_eq!(&records[1][1], "5");
+        assert_eq!(&records[1][7], "Add flags");
+    }
+
+    #[test]
+    fn test_walk_skips_gitignored_go_vendor() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join(".git")).unwrap();
+        fs::create_dir(dir.path().join("vendor")).unwrap();
+        fs::write(dir.path().join(".gitignore"), "vendor/\n").unwrap();
+        fs::write(dir.path().join("main.go"), "// TODO: Ship it\n").unwrap();
+        fs::write(dir.path().join("vendor/dep.go"), "// TODO: Not ours\n").unwrap();
+
+        let files: Vec<_> = WalkBuilder::new(dir.path())
+            .build()
+            .filter_map(|e| e.ok())
+            .filter(utils::is_supported_file)
+            .map(|e| e.into_path())
+            .collect();
+
+        assert_eq!(files, vec![dir.path().join("main.go")]);
+    }
+
+    #[test]
+    fn test_process_file_c_header() {
+        let records = scan_fixture(
+            "driver.h",
+            "#ifndef DRIVER_H\n#define DRIVER_H\n/* TODO: Document the register map */\n// TODO: Drop legacy init\n#endif\n",
+        );
+
+        assert_eq!(records.len(), 2);
+        assert_eq!(&records[0][1], "3");
+        assert_eq!(&records[0][7], "Document the register map");
+        assert_eq!(&records[1][1], "4");
+        assert_eq!(&records[1][7], "Drop legacy init");
+    }
+
+    #[test]
+    fn test_process_file_cpp_sources() {
+        for (file_name, line) in [
+            ("dma.cpp", "/* TODO: Use a ring buffer*/"),
+            ("dma.cc", "// TODO: Use a ring buffer"),
+            ("dma.cxx", "  /*TODO: Use a ring buffer   */  "),
+            ("dma.hpp", "/* TODO: Use a ring buffer */"),
+        ] {
+            let records = scan_fixture(file_name, &format!("#include <vector>\n{}\n", line));
+
+            assert_eq!(records.len(), 1, "{}", file_name);
+            assert_eq!(&records[0][1], "2");
+            assert_eq!(&records[0][7], "Use a ring buffer");
+        }
+    }
+
+    #[test]
+    fn test_process_file_ruby() {
+        for file_name in ["user.rb", "tasks.rake", "app.gemspec"] {
+            let records = scan_fixture(
+                file_name,
+                "class User\n  # TODO: fix N+1 query\n  def posts; end\nend\n",
+            );
+
+            assert_eq!(records.len(), 1, "{}", file_name);
+            assert_eq!(&records[0][1], "2");
+            assert_eq!(&records[0][7], "fix N+1 query");
+        }
+    }
+
+    #[test]
+    fn test_process_file_ruby_skips_embedded_docs() {
+        let records = scan_fixture(
+            "user.rb",
+            "=begin\n# TODO: this is documentation\n=end\n# TODO: cache the lookup\n",
+        );
+
+        assert_eq!(records.len(), 1);
+        assert_eq!(&records[0][1], "4");
+        assert_eq!(&records[0][7], "cache the lookup");
+    }
+
+    #[test]
+    fn test_process_file_php_comment_styles() {
+        let records = scan_fixture(
+            "index.php",
+            "<?php\n// TODO: Escape output\n# TODO: Use PDO\n/* TODO: Paginate results */\n?>\n<!-- TODO: Add a footer -->\n",
+        );
+
+        let comments: Vec<_> = records.iter().map(|r| (&r[1], &r[7])).collect();
+        assert_eq!(
+            comments,
+            vec![
+                ("2", "Escape output"),
+                ("3", "Use PDO"),
+                ("4", "Paginate results"),
+                ("6", "Add a footer"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_process_file_csharp() {
+        let records = scan_fixture(
+            "Service.cs",
+            concat!(
+                "using System;\n",
+                "#region Helpers\n",
+                "[Obsolete(\"Use V2\")]\n",
+                "/// TODO: Document the retry policy\n",
+                "public class Service {\n",
+                "    // TODO (alice): Move to config\n",
+                "    #endregion\n",
+                "    // TODO: Dispose the client\n",
+                "}\n",
+            ),
+        );
+
+        let comments: Vec<_> = records.iter().map(|r| (&r[1], &r[7])).collect();
+        assert_eq!(
+            comments,
+            vec![
+                ("4", "Document the retry policy"),
+                ("6", "Move to config"),
+                ("8", "Dispose the client"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_process_file_kotlin() {
+        let records = scan_fixture(
+            "Repository.kt",
+            "class Repository {\n    // TODO: Cache results\n}\n",
+        );
+        assert_eq!(records.len(), 1);
+        assert_eq!(&records[0][1], "2");
+        assert_eq!(&records[0][7], "Cache results");
+
+        let records = scan_fixture(
+            "build.gradle.kts",
+            concat!(
+                "dependencies {\n",
+                "    // TODO: unpin this version\n",
+                "    implementation(\"com.squareup.okhttp3:okhttp:4.9.0\")\n",
+                "    // TODO: drop once migrated\n",
+                "    implementation(\"junit:junit:4.13\")\n",
+                "}\n",
+            ),
+        );
+        let comments: Vec<_> = records.iter().map(|r| (&r[1], &r[7])).collect();
+        assert_eq!(
+            comments,
+            vec![("2", "unpin this version"), ("4", "drop once migrated")]
+        );
+    }
+
+    #[test]
+    fn test_process_file_swift_marker() {
+        let records = scan_fixture(
+            "ViewController.swift",
+            "import UIKit\n// TODO: - Extract the data source\n// TODO: Support dark mode\n// TODO: -1 is a sentinel\n",
+        );
+
+        let comments: Vec<_> = records.iter().map(|r| (&r[1], &r[7])).collect();
+        assert_eq!(
+            comments,
+            vec![
+                ("2", "Extract the data source"),
+                ("3", "Support dark mode"),
+                ("4", "-1 is a sentinel"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_process_file_scala() {
+        let records = scan_fixture(
+            "Parser.scala",
+            "object Parser {\n  /* TODO: Handle escapes */\n  // TODO: Report positions\n}\n",
+        );
+        assert_eq!(records.len(), 2);
+        assert!(records[0][0].ends_with("Parser.scala"));
+        assert_eq!((&records[0][1], &records[0][7]), ("2", "Handle escapes"));
+        assert_eq!((&records[1][1], &records[1][7]), ("3", "Report positions"));
+
+        let records = scan_fixture(
+            "build.sbt",
+            "name := \"parser\"\n// TODO: Cross-build for 3.x\nscalaVersion := \"2.13.10\"\n",
+        );
+        assert_eq!(records.len(), 1);
+        assert!(records[0][0].ends_with("build.sbt"));
+        assert_eq!(
+            (&records[0][1], &records[0][7]),
+            ("2", "Cross-build for 3.x")
+        );
+    }
+
+    #[test]
+    fn test_process_file_shell_shebang() {
+        for file_name in ["deploy.sh", "deploy.bash", "deploy.zsh", "deploy.fish"] {
+            let records = scan_fixture(
+                file_name,
+                "#!/bin/bash\n# TODO: Fail fast on errors\nset -u\n",
+            );
+
+            assert_eq!(records.len(), 1, "{}", file_name);
+            assert_eq!(&records[0][1], "2");
+            assert_eq!(&records[0][7], "Fail fast on errors");
+        }
+
+        assert!(scan_fixture("deploy.sh", "#!/usr/bin/env TODO: bash\n").is_empty());
+    }
+
+    #[test]
+    fn test_process_file_lua() {
+        let records = scan_fixture(
+            "worker.lua",
+            concat!(
+                "local M = {}\n",
+                "-- TODO: rewrite this coroutine\n",
+                "--[[ TODO: not a line comment ]]\n",
+                "--[[\n",
+                "  block comment\n",
+                "]]\n",
+                "# TODO: not a Lua comment\n",
+                "return M\n",
+            ),
+        );
+
+        assert_eq!(records.len(), 1);
+        assert_eq!(&records[0][1], "2");
+        assert_eq!(&records[0][7], "rewrite this coroutine");
+    }
+
+    #[test]
+    fn test_process_file_haskell() {
+        let records = scan_fixture(
+            "Main.hs",
+            "module Main where\n\n-- TODO: Parse arguments\nmain :: IO ()\n{- TODO: block comments are skipped -}\n",
+        );
+
+        assert_eq!(records.len(), 1);
+        assert_eq!(&records[0][1], "3");
+        assert_eq!(&records[0][7], "Parse arguments");
+    }
+
+    #[test]
+    fn test_process_file_literate_haskell() {
+        let records = scan_fixture(
+            "Main.lhs",
+            "-- TODO: prose, not code\n\n> -- TODO: Parse arguments\n> main :: IO ()\n",
+        );
+
+        assert_eq!(records.len(), 1);
+        assert_eq!(&records[0][1], "3");
+        assert_eq!(&records[0][7], "Parse arguments");
+    }
+
+    #[test]
+    fn test_process_file_sql_migration() {
+        let mut migration = String::from("BEGIN;\n");
+        for i in 0..500 {
+            migration.push_str(&format!("INSERT INTO users VALUES ({}, 'user');\n", i));
+        }
+        migration.push_str("SELECT '-- TODO: not a comment' FROM users;\n");
+        migration.push_str("  -- TODO: backfill this column\n");
+        migration.push_str("COMMIT;\n");
+
+        let records = scan_fixture("0042_users.sql", &migration);
+
+        assert_eq!(records.len(), 1);
+        assert_eq!(&records[0][1], "503");
+        assert_eq!(&records[0][7], "backfill this column");
+    }
+
+    #[test]
+    fn test_process_file_config_files() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join("docker-compose.yml"),
+            "services:\n  api:\n    # TODO: bump memory limit\n    mem_limit: 512m\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join("values.yaml"),
+            "# TODO: Pin the chart version\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join("Cargo.toml"),
+            "[package]\nname = \"demo\"\n# TODO: Publish to crates.io\n",
+        )
+        .unwrap();
+
+        let mut files: Vec<_> = WalkBuilder::new(dir.path())
+            .build()
+            .filter_map(|e| e.ok())
+            .filter(utils::is_supported_file)
+            .map(|e| e.into_path())
+            .collect();
+        files.sort();
+        let paths: Vec<_> = files.iter().map(|p| p.as_path()).collect();
+        let records = process_files(&paths, dir.path());
+
+        let comments: Vec<_> = records.iter().map(|r| (&r[1], &r[7])).collect();
+        assert_eq!(
+            comments,
+            vec![
+                ("3", "Publish to crates.io"),
+                ("3", "bump memory limit"),
+                ("1", "Pin the chart version"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_process_file_html_comments() {
+        for file_name in ["index.html", "index.htm", "page.xhtml", "pom.xml"] {
+            let records = scan_fixture(
+                file_name,
+                concat!(
+                    "<div>\n",
+                    "  <!-- TODO: replace placeholder copy -->\n",
+                    "  <!--TODO: tighten spacing-->\n",
+                    "  <!-- TODO: add alt text --> <img src=\"a.png\">\n",
+                    "  <p>TODO: not a comment</p>\n",
+                    "</div>\n",
+                ),
+            );
+
+            let comments: Vec<_> = records.iter().map(|r| (&r[1], &r[7])).collect();
+            assert_eq!(
+                comments,
+                vec![
+                    ("2", "replace placeholder copy"),
+                    ("3", "tighten spacing"),
+                    ("4", "add alt text"),
+                ],
+                "{}",
+                file_name
+            );
+        }
+    }
+
+    #[test]
+    fn test_process_file_stylesheets() {
+        let records = scan_fixture(
+            "site.css",
+            "body { margin: 0; }\n/* TODO: remove this !important hack */\n",
+        );
+        assert_eq!(records.len(), 1);
+        assert_eq!(
+            (&records[0][1], &records[0][7]),
+            ("2", "remove this !important hack")
+        );
+
+        for file_name in ["theme.scss", "theme.less"] {
+            let records = scan_fixture(
+                file_name,
+                "// TODO: Use variables\n/* TODO: Drop vendor prefixes */\n",
+            );
+            let comments: Vec<_> = records.iter().map(|r| (&r[1], &r[7])).collect();
+            assert_eq!(
+                comments,
+                vec![("1", "Use variables"), ("2", "Drop vendor prefixes")],
+                "{}",
+                file_name
+            );
+        }
+    }
+
+    #[test]
+    fn test_process_file_minified_css() {
+        let minified = ".a{color:red}".repeat(50_000);
+        let records = scan_fixture(
+            "site.min.css",
+            &format!("{}\n/* TODO: Ship source maps */\n", minified),
+        );
+
+        assert_eq!(records.len(), 1);
+        assert_eq!((&records[0][1], &records[0][7]), ("2", "Ship source maps"));
+    }
+
+    #[test]
+    fn test_process_file_markdown() {
+        for file_name in ["README.md", "guide.mdx"] {
+            let records = scan_fixture(
+                file_name,
+                concat!(
+                    "# Guide\n",
+                    "<!-- TODO: update screenshots -->\n",
+                    "- [ ] TODO: document the config file\n",
+                    "- [x] TODO: already done\n",
+                    "* [ ] TODO: add a FAQ\n",
+                    "```rust\n",
+                    "// TODO: example snippet\n",
+                    "```\n",
+                    "~~~\n",
+                    "# TODO: another example\n",
+                    "~~~\n",
+                    "- [ ] Not a TODO item\n",
+                ),
+            );
+
+            let comments: Vec<_> = records.iter().map(|r| (&r[1], &r[7])).collect();
+            assert_eq!(
+                comments,
+                vec![
+                    ("2", "update screenshots"),
+                    ("3", "document the config file"),
+                    ("5", "add a FAQ"),
+                ],
+                "{}",
+                file_name
+            );
+        }
+    }
+
+    #[test]
+    fn test_process_file_known_filenames() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join("Dockerfile"),
+            "FROM rust:1.70\n# TODO: Use a slim base image\n",
+        )
+        .unwrap();
+        fs::write(dir.path().join("Dockerfile.prod"), "# TODO: Drop root\n").unwrap();
+        fs::write(dir.path().join("Makefile"), "all:\n\t# TODO: Add lint\n").unwrap();
+        fs::write(
+            dir.path().join("justfile"),
+            "# TODO: Add a release recipe\n",
+        )
+        .unwrap();
+        fs::write(dir.path().join("LICENSE"), "# TODO: not source\n").unwrap();
+
+        let mut files: Vec<_> = WalkBuilder::new(dir.path())
+            .build()
+            .filter_map(|e| e.ok())
+            .filter(utils::is_supported_file)
+            .map(|e| e.into_path())
+            .collect();
+        files.sort();
+        let paths: Vec<_> = files.iter().map(|p| p.as_path()).collect();
+        let records = process_files(&paths, dir.path());
+
+        let comments: Vec<_> = records.iter().map(|r| (&r[1], &r[7])).collect();
+        assert_eq!(
+            comments,
+            vec![
+                ("2", "Use a slim base image"),
+                ("1", "Drop root"),
+                ("2", "Add lint"),
+                ("1", "Add a release recipe"),
+            ]
+        );
+        assert!(records[0][0].ends_with("Dockerfile"));
+    }
+
+    #[test]
+    fn test_process_file_perl_with_pod() {
+        let records = scan_fixture(
+            "Parser.pm",
+            concat!(
+                "package Parser;\n",
+                "# TODO: Enable strict mode\n",
+                "\n",
+                "=pod\n",
+                "\n",
+                "Parses things.\n",
+                "\n",
+                "=cut\n",
+                "\n",
+                "sub parse {\n",
+                "    # TODO: Handle empty input\n",
+                "}\n",
+            ),
+        );
+
+        let comments: Vec<_> = records.iter().map(|r| (&r[1], &r[7])).collect();
+        assert_eq!(
+            comments,
+            vec![("2", "Enable strict mode"), ("11", "Handle empty input")]
+        );
+        assert_eq!(scan_fixture("script.pl", "# TODO: Add usage\n").len(), 1);
+    }
+
+    #[test]
+    fn test_process_file_elixir() {
+        for file_name in ["worker.ex", "worker_test.exs"] {
+            let records = scan_fixture(
+                file_name,
+                "defmodule Worker do\n  # TODO: Supervise this\nend\n",
+            );
+
+            assert_eq!(records.len(), 1, "{}", file_name);
+            assert_eq!((&records[0][1], &records[0][7]), ("2", "Supervise this"));
+        }
+    }
+
+    #[test]
+    fn test_process_file_erlang() {
+        let records = scan_fixture(
+            "server.erl",
+            concat!(
+                "-module(server).\n",
+                "% TODO: handle timeout\n",
+                "%% TODO: export fewer functions\n",
+                "%%% TODO: split the module\n",
+                "# TODO: not an Erlang comment\n",
+            ),
+        );
+
+        let comments: Vec<_> = records.iter().map(|r| (&r[1], &r[7])).collect();
+        assert_eq!(
+            comments,
+            vec![
+                ("2", "handle timeout"),
+                ("3", "export fewer functions"),
+                ("4", "split the module"),
+            ]
+        );
+        assert_eq!(
+            scan_fixture("records.hrl", "%% TODO: Add a field\n").len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_process_file_dart() {
+        let records = scan_fixture(
+            "home_page.dart",
+            concat!(
+                "import 'package:flutter/material.dart';\n",
+                "/// TODO: Document the widget\n",
+                "class HomePage extends StatelessWidget {\n",
+                "  // TODO(alice): Extract the theme\n",
+                "}\n",
+            ),
+        );
+
+        let comments: Vec<_> = records.iter().map(|r| (&r[1], &r[7])).collect();
+        assert_eq!(
+            comments,
+            vec![("2", "Document the widget"), ("4", "Extract the theme")]
+        );
+    }
+
+    #[test]
+    fn test_process_file_single_file_components() {
+        for file_name in ["Counter.vue", "Counter.svelte"] {
+            let records = scan_fixture(
+                file_name,
+                concat!(
+                    "<template>\n",
+                    "  <!-- TODO: Add an aria label -->\n",
+                    "  <button @click=\"inc\">{{ count }}</button>\n",
+                    "</template>\n",
+                    "<script>\n",
+                    "// TODO: Persist the count\n",
+                    "export default { data: () => ({ count: 0 }) };\n",
+                    "</script>\n",
+                    "<style>\n",
+                    "/* TODO: Use theme colors */\n",
+                    "</style>\n",
+                ),
+            );
+
+            let comments: Vec<_> = records.iter().map(|r| (&r[1], &r[7])).collect();
+            assert_eq!(
+                comments,
+                vec![
+                    ("2", "Add an aria label"),
+                    ("6", "Persist the count"),
+                    ("10", "Use theme colors"),
+                ],
+                "{}",
+                file_name
+            );
+        }
+    }
+
+    #[test]
+    fn test_process_file_javascript_variants() {
+        for file_name in [
+            "App.tsx",
+            "index.d.ts",
+            "worker.mts",
+            "App.jsx",
+            "server.mjs",
+            "config.cjs",
+        ] {
+            let records = scan_fixture(file_name, "export const x = 1;\n// TODO: Add types\n");
+
+            assert_eq!(records.len(), 1, "{}", file_name);
+            assert_eq!((&records[0][1], &records[0][7]), ("2", "Add types"));
+        }
+    }
+
+    #[test]
+    fn test_process_file_r() {
+        for file_name in ["analysis.R", "helpers.r"] {
+            let records = scan_fixture(file_name, "library(dplyr)\n# TODO: Drop outliers\n");
+
+            assert_eq!(records.len(), 1, "{}", file_name);
+            assert_eq!((&records[0][1], &records[0][7]), ("2", "Drop outliers"));
+        }
+    }
+
+    #[test]
+    fn test_r_markdown_is_not_supported() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join("report.Rmd"),
+            "```{r}\n# TODO: Not scanned\n```\n",
+        )
+        .unwrap();
+
+        let files: Vec<_> = WalkBuilder::new(dir.path())
+            .build()
+            .filter_map(|e| e.ok())
+            .filter(utils::is_supported_file)
+            .collect();
+
+        assert!(files.is_empty());
+    }
+
+    #[test]
+    fn test_process_file_notebook() {
+        let records = scan_fixture(
+            "analysis.ipynb",
+            r##"{
+                "cells": [
+                    {"cell_type": "markdown", "source": ["# TODO: Not code\n"]},
+                    {"cell_type": "code", "source": ["import pandas as pd\n", "# TODO: Pin pandas\n"]},
+                    {"cell_type": "code", "source": "df = load()\ndf.head()\n# TODO: Drop nulls"}
+                ],
+                "metadata": {},
+                "nbformat": 4,
+                "nbformat_minor": 5
+            }"##,
+        );
+
+        let comments: Vec<_> = records.iter().map(|r| (&r[1], &r[7])).collect();
+        assert_eq!(
+            comments,
+            vec![
+                ("cell 2, line 2", "Pin pandas"),
+                ("cell 3, line 3", "Drop nulls"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_process_file_notebook_context() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("analysis.ipynb");
+        fs::write(
+            &path,
+            r##"{
+                "cells": [
+                    {"cell_type": "code", "source": ["import pandas as pd\n", "# TODO: Pin pandas\n"]},
+                    {"cell_type": "code", "source": ["df = load()\n", "df.head()\n"]}
+                ]
+            }"##,
+        )
+        .unwrap();
+
+        let scanner = utils::ScannerBuilder::new().context(1).build();
+        let mut writer = output::CsvWriter::new(Vec::new(), vec![output::Column::Context]);
+        scanner.process_file(&path, &mut writer).unwrap();
+        writer.finish().unwrap();
+
+        assert_eq!(
+            String::from_utf8(writer.into_inner().unwrap()).unwrap(),
+            "\"import pandas as pd\n# TODO: Pin pandas\"\n"
+        );
+    }
+
+    #[test]
+    fn test_process_file_notebook_min_age_days() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("analysis.ipynb");
+        fs::write(
+            &path,
+            r##"{"cells": [{"cell_type": "code", "source": ["# TODO: Pin pandas\n"]}]}"##,
+        )
+        .unwrap();
+        let scan = |min_age_days| {
+            let scanner = utils::ScannerBuilder::new()
+                .age_reference(chrono::NaiveDate::from_ymd_opt(2024, 6, 5))
+                .min_age_days(min_age_days)
+                .build();
+            let mut writer = output::CsvWriter::new(Vec::new(), scanner.columns());
+            scanner.process_file(&path, &mut writer).unwrap();
+            writer.finish().unwrap();
+            writer.into_inner().unwrap()
+        };
+
+        assert!(!scan(None).is_empty());
+        // Notebook TODOs have no git history, so they have no age either.
+        assert!(scan(Some(0)).is_empty());
+    }
+
+    #[test]
+    fn test_process_file_malformed_notebook() {
+        assert!(scan_fixture("broken.ipynb", "{\"cells\": [").is_empty());
+        assert!(scan_fixture("empty.ipynb", "{}").is_empty());
+    }
+
+    #[test]
+    fn test_process_file_powershell_crlf() {
+        for file_name in ["deploy.ps1", "Tools.psm1", "Tools.psd1"] {
+            let records = scan_fixture(
+                file_name,
+                concat!(
+                    "param([string]$Name)\r\n",
+                    "# TODO: Validate the name\r\n",
+                    "<# TODO: Write help #>\r\n",
+                    "<#\r\n",
+                    "  # TODO: Inside a block\r\n",
+                    "#>\r\n",
+                ),
+            );
+
+            let comments: Vec<_> = records.iter().map(|r| (&r[1], &r[7])).collect();
+            assert_eq!(
+                comments,
+                vec![
+                    ("2", "Validate the name"),
+                    ("3", "Write help"),
+                    ("5", "Inside a block"),
+                ],
+                "{}",
+                file_name
+            );
+        }
+    }
+
+    #[test]
+    fn test_process_file_zig_doc_comments() {
+        let records = scan_fixture(
+            "main.zig",
+            concat!(
+                "//! TODO: Describe the module\n",
+                "const std = @import(\"std\");\n",
+                "/// TODO: Document main\n",
+                "pub fn main() void {\n",
+                "    // TODO: Parse args\n",
+                "}\n",
+            ),
+        );
+
+        let comments: Vec<_> = records.iter().map(|r| (&r[1], &r[7])).collect();
+        assert_eq!(
+            comments,
+            vec![
+                ("1", "Describe the module"),
+                ("3", "Document main"),
+                ("5", "Parse args"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_process_file_lisp_family() {
+        for file_name in [
+            "core.clj",
+            "app.cljs",
+            "config.edn",
+            "init.el",
+            "utils.lisp",
+        ] {
+            let records = scan_fixture(
+                file_name,
+                concat!(
+                    "(ns core)\n",
+                    "; TODO: Add docstrings\n",
+                    ";; TODO: memoize this\n",
+                    ";;; TODO: Split the namespace\n",
+                    "(defn f [x] x) ; TODO: not at line start\n",
+                ),
+            );
+
+            let comments: Vec<_> = records.iter().map(|r| (&r[1], &r[7])).collect();
+            assert_eq!(
+                comments,
+                vec![
+                    ("2", "Add docstrings"),
+                    ("3", "memoize this"),
+                    ("4", "Split the namespace"),
+                ],
+                "{}",
+                file_name
+            );
+        }
+    }
+
+    #[test]
+    fn test_process_file_vim_script() {
+        for file_name in ["plugin.vim", "vimrc", ".vimrc"] {
+            let records = scan_fixture(
+                file_name,
+                concat!(
+                    "set nocompatible\n",
+                    "\" TODO: Move mappings to a plugin\n",
+                    "let s = \"TODO: not a comment\"\n",
+                    "  \"TODO: Lazy-load fzf\n",
+                ),
+            );
+
+            let comments: Vec<_> = records.iter().map(|r| (&r[1], &r[7])).collect();
+            assert_eq!(
+                comments,
+                vec![("2", "Move mappings to a plugin"), ("4", "Lazy-load fzf")],
+                "{}",
+                file_name
+            );
+        }
+    }
+
+    #[test]
+    fn test_process_file_latex() {
+        let records = scan_fixture(
+            "paper.tex",
+            concat!(
+                "\\documentclass{article}\n",
+                "\\usepackage{amsmath}\n",
+                "% TODO: cite the 2019 paper\n",
+                "\\begin{document}\n",
+                "Accuracy rose by 5\\% TODO: not a comment\n",
+                "  %% TODO: Add the results table\n",
+                "\\end{document}\n",
+            ),
+        );
+
+        let comments: Vec<_> = records.iter().map(|r| (&r[1], &r[7])).collect();
+        assert_eq!(
+            comments,
+            vec![("3", "cite the 2019 paper"), ("6", "Add the results table")]
+        );
+        assert_eq!(scan_fixture("refs.bib", "% TODO: Add DOI\n").len(), 1);
+    }
+
+    #[test]
+    fn test_process_file_fortran() {
+        let records = scan_fixture(
+            "solver.f90",
+            concat!(
+                "program solver\n",
+                "  ! TODO: vectorize this loop\n",
+                "  do i = 1, n\n",
+                "  ! todo: check bounds\n",
+                "  ! Note the loop order matters\n",
+                "  !$omp parallel\n",
+                "  end do\n",
+                "end program solver ! TODO: not at line start\n",
+            ),
+        );
+
+        let rows: Vec<_> = records.iter().map(|r| (&r[1], &r[2], &r[7])).collect();
+        assert_eq!(
+            rows,
+            vec![
+                ("2", "TODO", "vectorize this loop"),
+                ("4", "TODO", "check bounds")
+            ]
+        );
+        assert!(scan_fixture("main.rs", "// todo: lowercase\n").is_empty());
+        assert!(scan_fixture("solver.f90", "! fixme: still case-sensitive\n").is_empty());
+    }
+
+    #[test]
+    fn test_process_file_groovy() {
+        for file_name in [
+            "build.gradle",
+            "Utils.groovy",
+            "Jenkinsfile",
+            "Jenkinsfile.release",
+        ] {
+            let records = scan_fixture(
+                file_name,
+                "plugins {\n    id 'java'\n}\n// TODO: migrate to version catalog\n",
+            );
+
+            assert_eq!(records.len(), 1, "{}", file_name);
+            assert_eq!(
+                (&records[0][1], &records[0][7]),
+                ("4", "migrate to version catalog")
+            );
+        }
+    }
+
+    #[test]
+    fn test_process_file_fixme_tag() {
+        let records = scan_fixture(
+            "lib.rs",
+            "// TODO: Add retries\nfn f() {}\n// FIXME: Leaks the handle\n# FIXME: not Rust\n",
+        );
+
+        let rows: Vec<_> = records.iter().map(|r| (&r[1], &r[2], &r[7])).collect();
+        assert_eq!(
+            rows,
+            vec![
+                ("1", "TODO", "Add retries"),
+                ("3", "FIXME", "Leaks the handle"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_process_file_default_keywords() {
+        assert_eq!(
+            utils::default_keywords(),
+            ["TODO", "FIXME", "HACK", "XXX", "NOTE", "BUG", "OPTIMIZE"]
+        );
+
+        let records = scan_fixture(
+            "lib.rs",
+            concat!(
+                "// HACK: Work around the borrow checker\n",
+                "// XXX: Racy\n",
+                "// NOTE: Called from the signal handler\n",
+                "// BUG: Off by one\n",
+                "// OPTIMIZE: Avoid the clone\n",
+                "// Note: not uppercase\n",
+            ),
+        );
+
+        let tags: Vec<_> = records.iter().map(|r| &r[2]).collect();
+        assert_eq!(tags, vec!["HACK", "XXX", "NOTE", "BUG", "OPTIMIZE"]);
+    }
+
+    #[test]
+    fn test_scanner_with_trimmed_keywords() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("lib.rs");
+        fs::write(&path, "// TODO: Keep\n// NOTE: Drop\n// BUG: Keep too\n").unwrap();
+        let scanner = utils::ScannerBuilder::new()
+            .keywords(["TODO", "BUG"])
+            .build();
+
+        let output = dir.path().join("out.csv");
+        let mut csv_writer = Writer::from_path(&output).unwrap();
+        scanner.process_file(&path, &mut csv_writer).unwrap();
+        csv_writer.flush().unwrap();
+
+        let contents = fs::read_to_string(&output).unwrap();
+        let tags: Vec<_> = contents
+            .lines()
+            .map(|line| line.split(',').nth(2).unwrap())
+            .collect();
+        assert_eq!(tags, vec!["TODO", "BUG"]);
+    }
+
+    #[test]
+    fn test_process_file_with_keywords() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("main.cpp");
+        fs::write(
+            &path,
+            "// TODO: Keep\n// FIXME: Not requested\n// C++: Use std::span\n// SECURITY: Validate input\n",
+        )
+        .unwrap();
+        let output = dir.path().join("out.csv");
+
+        let mut csv_writer = Writer::from_path(&output).unwrap();
+        utils::process_file_with_keywords(&path, &mut csv_writer, &["TODO"]).unwrap();
+        utils::process_file_with_keywords(&path, &mut csv_writer, &["C++", "SECURITY"]).unwrap();
+        csv_writer.flush().unwrap();
+
+        let records: Vec<_> = ReaderBuilder::new()
+            .has_headers(false)
+            .from_path(&output)
+            .unwrap()
+            .records()
+            .map(Result::unwrap)
+            .collect();
+        let rows: Vec<_> = records.iter().map(|r| (&r[2], &r[7])).collect();
+        assert_eq!(
+            rows,
+            vec![
+                ("TODO", "Keep"),
+                ("C++", "Use std::span"),
+                ("SECURITY", "Validate input"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_scanner_ignore_case() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("app.py");
+        fs::write(&path, "# todo: fix Later\n# Todo: Revisit\n# TODO: Keep\n").unwrap();
+        let output = dir.path().join("out.csv");
+
+        let mut csv_writer = Writer::from_path(&output).unwrap();
+        utils::ScannerBuilder::new()
+            .keywords(["TODO"])
+            .ignore_case(true)
+            .build()
+            .process_file(&path, &mut csv_writer)
+            .unwrap();
+        csv_writer.flush().unwrap();
+
+        assert_eq!(
+            fs::read_to_string(&output).unwrap(),
+            format!(
+                "{0},1,TODO,,,,,fix Later\n{0},2,TODO,,,,,Revisit\n{0},3,TODO,,,,,Keep\n",
+                path.display()
+            )
+        );
+        assert_eq!(
+            scan_fixture("app.py", "# todo: fix later\n# TODO: Keep\n").len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_process_file_optional_separator() {
+        let records = scan_fixture(
+            "lib.rs",
+            concat!(
+                "// TODO fix\n",
+                "// TODO: fix\n",
+                "// TODO- fix\n",
+                "// TODO - fix\n",
+                "// TODOLIST is a type\n",
+                "// TODO\n",
+            ),
+        );
+
+        let comments: Vec<_> = records.iter().map(|r| (&r[1], &r[7])).collect();
+        assert_eq!(
+            comments,
+            vec![("1", "fix"), ("2", "fix"), ("3", "fix"), ("4", "fix")]
+        );
+    }
+
+    #[test]
+    fn test_process_file_rust_macros() {
+        let records = scan_fixture(
+            "client.rs",
+            concat!(
+                "fn retry() -> u32 {\n",
+                "    todo!(\"wire up retry logic\")\n",
+                "}\n",
+                "fn backoff() { todo!() }\n",
+                "fn jitter() -> u64 { unimplemented!(\"needs \\\"rand\\\"\") }\n",
+                "/// Panics with `todo!()` for now.\n",
+                "fn mytodo!() {}\n",
+                "let hint = \"replace todo!() before release\";\n",
+            ),
+        );
+
+        let rows: Vec<_> = records.iter().map(|r| (&r[1], &r[2], &r[7])).collect();
+        assert_eq!(
+            rows,
+            vec![
+                ("2", "todo!", "wire up retry logic"),
+                ("4", "todo!", ""),
+                ("5", "unimplemented!", r#"needs \"rand\""#),
+            ]
+        );
+        assert!(scan_fixture("app.py", "todo!()\n").is_empty());
+    }
+
+    #[test]
+    fn test_process_file_doc_tags() {
+        let javadoc = concat!(
+            "/**\n",
+            " * Resolves handlers.\n",
+            " *\n",
+            " * @todo refactor this into a strategy\n",
+            " * @TODO Cache lookups\n",
+            " * @note Not thread-safe.\n",
+            " * @bug 4711\n",
+            " * @param name the handler name\n",
+            " */\n",
+            "/** @todo Inline this */\n",
+        );
+        for file_name in [
+            "Resolver.java",
+            "resolver.ts",
+            "resolver.js",
+            "resolver.php",
+        ] {
+            let records = scan_fixture(file_name, javadoc);
+
+            let rows: Vec<_> = records.iter().map(|r| (&r[1], &r[2], &r[7])).collect();
+            assert_eq!(
+                rows,
+                vec![
+                    ("4", "TODO", "refactor this into a strategy"),
+                    ("5", "TODO", "Cache lookups"),
+                    ("10", "TODO", "Inline this"),
+                ],
+                "{}",
+                file_name
+            );
+        }
+        assert!(scan_fixture("resolver.rs", javadoc).is_empty());
+    }
+
+    #[test]
+    fn test_process_file_author() {
+        let records = scan_fixture(
+            "api.go",
+            concat!(
+                "// TODO(alice): migrate to v2 API\n",
+                "// TODO(alice) drop the shim\n",
+                "// TODO (bob): Add metrics\n",
+                "// TODO: unowned\n",
+            ),
+        );
+
+        let rows: Vec<_> = records.iter().map(|r| (&r[3], &r[7])).collect();
+        assert_eq!(
+            rows,
+            vec![
+                ("alice", "migrate to v2 API"),
+                ("alice", "drop the shim"),
+                ("bob", "Add metrics"),
+                ("", "unowned"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_extract_todo() {
+        let pattern =
+            Regex::new(r"^//\s*(?P<tag>TODO)(?:\((?P<author>[^)]*)\))?:\s*(?P<text>.*)$").unwrap();
+
+        assert_eq!(
+            utils::extract_todo("// TODO(alice): Fix", &pattern),
+            Some(utils::Todo {
+                column: 4,
+                tag: String::from("TODO"),
+                author: Some(String::from("alice")),
+                issue: None,
+                priority: None,
+                due: None,
+                overdue: None,
+                doc: false,
+                comment: String::from("Fix"),
+            })
+        );
+        assert_eq!(
+            utils::extract_todo("    // TODO: Implement the new feature", &TODO_PATTERN)
+                .map(|todo| (todo.tag, todo.author)),
+            Some((String::from("TODO"), None))
+        );
+    }
+
+    #[test]
+    fn test_process_file_issue_references() {
+        let records = scan_fixture(
+            "flags.rs",
+            concat!(
+                "// TODO: remove after #4521 ships\n",
+                "// TODO: PROJ-1234 clean up flag, see also PROJ-99\n",
+                "// TODO: no ticket, C-3PO is not an issue\n",
+                "fn f() { todo!(\"blocked on #12\") }\n",
+            ),
+        );
+
+        let rows: Vec<_> = records.iter().map(|r| (&r[4], &r[7])).collect();
+        assert_eq!(
+            rows,
+            vec![
+                ("#4521", "remove after #4521 ships"),
+                ("PROJ-1234", "PROJ-1234 clean up flag, see also PROJ-99"),
+                ("", "no ticket, C-3PO is not an issue"),
+                ("#12", "blocked on #12"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_scanner_custom_issue_pattern() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("lib.rs");
+        fs::write(&path, "// TODO: fix bug 881 not #12\n").unwrap();
+        let output = dir.path().join("out.csv");
+
+        let mut csv_writer = Writer::from_path(&output).unwrap();
+        utils::ScannerBuilder::new()
+            .issue_pattern(Some(Regex::new(r"bug \d+").unwrap()))
+            .build()
+            .process_file(&path, &mut csv_writer)
+            .unwrap();
+        csv_writer.flush().unwrap();
+
+        assert_eq!(
+            fs::read_to_string(&output).unwrap(),
+            format!("{},1,TODO,,bug 881,,,fix bug 881 not #12\n", path.display())
+        );
+    }
+
+    #[test]
+    fn test_process_file_priority() {
+        let records = scan_fixture(
+            "release.ts",
+            concat!(
+                "// TODO!: drop this before GA\n",
+                "// TODO!!!: fix the data loss\n",
+                "// TODO(P2): tidy the logs\n",
+                "// FIXME!!(alice): flaky\n",
+                "// TODO: this is bad!\n",
+            ),
+        );
+
+        let rows: Vec<_> = records.iter().map(|r| (&r[3], &r[5], &r[7])).collect();
+        assert_eq!(
+            rows,
+            vec![
+                ("", "1", "drop this before GA"),
+                ("", "3", "fix the data loss"),
+                ("", "P2", "tidy the logs"),
+                ("alice", "2", "flaky"),
+                ("", "", "this is bad!"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_process_file_due_dates() {
+        let records = scan_fixture(
+            "compat.rs",
+            concat!(
+                "// TODO(2025-03-01): remove the compatibility shim\n",
+                "// TODO: drop the flag by 2024-12-31\n",
+                "// TODO(2025-02-30): not a real date\n",
+                "// TODO: no date\n",
+            ),
+        );
+
+        let rows: Vec<_> = records.iter().map(|r| (&r[3], &r[6], &r[7])).collect();
+        assert_eq!(
+            rows,
+            vec![
+                ("", "2025-03-01", "remove the compatibility shim"),
+                ("", "2024-12-31", "drop the flag by 2024-12-31"),
+                ("", "", "(2025-02-30) not a real date"),
+                ("", "", "no date"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_scanner_flags_overdue_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("lib.rs");
+        fs::write(
+            &path,
+            "// TODO(2025-03-01): late\n// TODO(2025-06-01): on time\n// TODO: undated\n",
+        )
+        .unwrap();
+        let output = dir.path().join("out.csv");
+        let scanner = utils::ScannerBuilder::new()
+            .reference_date(chrono::NaiveDate::from_ymd_opt(2025, 4, 1))
+            .build();
+
+        let mut csv_writer =
+            output::CsvWriter::new(fs::File::create(&output).unwrap(), scanner.columns());
+        csv_writer.write_header().unwrap();
+        scanner.process_file(&path, &mut csv_writer).unwrap();
+        csv_writer.finish().unwrap();
+
+        let records: Vec<_> = ReaderBuilder::new()
+            .from_path(&output)
+            .unwrap()
+            .records()
+            .map(Result::unwrap)
+            .collect();
+        let rows: Vec<_> = records.iter().map(|r| (&r[7], &r[8])).collect();
+        assert_eq!(
+            rows,
+            vec![("true", "late"), ("false", "on time"), ("", "undated")]
+        );
+        assert_eq!(utils::Scanner::default().columns(), output::Column::DEFAULT);
+    }
+
+    #[test]
+    fn test_scanner_reports_aliases_under_canonical_tag() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("lib.rs");
+        fs::write(
+            &path,
+            "// REVIEW: check the bounds\n// needswork: split this up\n// TODO: plain\n",
+        )
+        .unwrap();
+        let output = dir.path().join("out.csv");
+        let scanner = utils::ScannerBuilder::new()
+            .ignore_case(true)
+            .aliases(HashMap::from([
+                ("REVIEW".to_string(), "TODO".to_string()),
+                ("NEEDSWORK".to_string(), "FIXME".to_string()),
+            ]))
+            .build();
+
+        let mut csv_writer = Writer::from_path(&output).unwrap();
+        scanner.process_file(&path, &mut csv_writer).unwrap();
+        csv_writer.flush().unwrap();
+
+        let records: Vec<_> = ReaderBuilder::new()
+            .has_headers(false)
+            .from_path(&output)
+            .unwrap()
+            .records()
+            .map(Result::unwrap)
+            .collect();
+        let rows: Vec<_> = records.iter().map(|r| (&r[2], &r[7])).collect();
+        assert_eq!(
+            rows,
+            vec![
+                ("TODO", "check the bounds"),
+                ("FIXME", "split this up"),
+                ("TODO", "plain"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_json_output() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("main.py");
+        fs::write(&path, "x = 1\n# TODO(alice): handle #12 by 2025-03-01\n").unwrap();
+        set_modified(&path, 1_700_000_000);
+        let output = dir.path().join("out.json");
+
+        let mut writer = output::Format::Json
+            .writer(fs::File::create(&output).unwrap(), &Default::default())
+            .unwrap();
+        utils::Scanner::default()
+            .process_file(&path, &mut writer)
+            .unwrap();
+        writer.finish().unwrap();
+
+        let json: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&output).unwrap()).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!([{
+                "file": path.to_str().unwrap(),
+                "line": 2,
+                "cell": null,
+                "language": "Python",
+                "offset": 8,
+                "column": 3,
+                "tag": "TODO",
+                "author": "alice",
+                "issue": "#12",
+                "priority": null,
+                "due": "2025-03-01",
+                "overdue": null,
+                "doc": false,
+                "comment": "handle #12 by 2025-03-01",
+                "raw_line": null,
+                "blame_author": null,
+                "author_date": null,
+                "commit": null,
+                "age_days": null,
+                "modified": "2023-11-14T22:13:20Z",
+                "context": null,
+                "link": null,
+            }])
+        );
+    }
+
+    #[test]
+    fn test_json_output_empty_scan() {
+        let dir = tempfile::tempdir().unwrap();
+        let output = dir.path().join("out.json");
+
+        let mut writer = output::Format::Json
+            .writer(fs::File::create(&output).unwrap(), &Default::default())
+            .unwrap();
+        writer.finish().unwrap();
+
+        assert_eq!(fs::read_to_string(&output).unwrap(), "[]\n");
+    }
+
+    #[test]
+    fn test_json_lines_output() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("lib.rs");
+        fs::write(&path, "// TODO: first\nfn f() {}\n// FIXME: second\n").unwrap();
+        let output = dir.path().join("out.jsonl");
+
+        let mut writer = output::Format::JsonLines
+            .writer(fs::File::create(&output).unwrap(), &Default::default())
+            .unwrap();
+        utils::Scanner::default()
+            .process_file(&path, &mut writer)
+            .unwrap();
+        writer.finish().unwrap();
+
+        let lines: Vec<(u64, String)> = fs::read_to_string(&output)
+            .unwrap()
+            .lines()
+            .map(|line| {
+                let object: serde_json::Value = serde_json::from_str(line).unwrap();
+                let comment = object["comment"].as_str().unwrap().to_string();
+                (object["line"].as_u64().unwrap(), comment)
+            })
+            .collect();
+        assert_eq!(
+            lines,
+            vec![(1, "first".to_string()), (3, "second".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_markdown_output() {
+        let dir = tempfile::tempdir().unwrap();
+        let first = dir.path().join("a.rs");
+        let second = dir.path().join("b.py");
+        fs::write(
+            &first,
+            "// TODO: use `Vec<u8>` | [slice]\n// FIXME: *now*\n",
+        )
+        .unwrap();
+        fs::write(&second, "\n# TODO: plain\n").unwrap();
+        let output = dir.path().join("out.md");
+
+        let mut writer = output::Format::Markdown
+            .writer(fs::File::create(&output).unwrap(), &Default::default())
+            .unwrap();
+        for path in [&first, &second] {
+            utils::Scanner::default()
+                .process_file(path, &mut writer)
+                .unwrap();
+        }
+        writer.finish().unwrap();
+
+        assert_eq!(
+            fs::read_to_string(&output).unwrap(),
+            format!(
+                "### {}\n\n\
+                 - [ ] **L1**: use \\`Vec\\<u8\\>\\` \\| \\[slice\\]\n\
+                 - [ ] **L2**: \\*now\\*\n\
+                 \n\
+                 ### {}\n\n\
+                 - [ ] **L2**: plain\n",
+                first.display().to_string().replace('_', "\\_"),
+                second.display().to_string().replace('_', "\\_"),
+            )
+        );
+    }
+
+    #[test]
+    fn test_markdown_output_empty_scan() {
+        let mut buffer = Vec::new();
+        let mut writer = output::MarkdownWriter::new(&mut buffer);
+        writer.finish().unwrap();
+
+        assert_eq!(String::from_utf8(buffer).unwrap(), "No TODOs found.\n");
+    }
+
+    #[test]
+    fn test_html_report_matches_golden_file() {
+        let root = "tests/fixtures/report";
+        let options = output::OutputOptions {
+            root: root.to_string(),
+            ..Default::default()
+        };
+        let dir = tempfile::tempdir().unwrap();
+        let output = dir.path().join("report.html");
+
+        let mut writer = output::Format::Html
+            .writer(fs::File::create(&output).unwrap(), &options)
+            .unwrap();
+        for file in ["build.py", "src/main.rs"] {
+            utils::Scanner::default()
+                .process_file(&Path::new(root).join(file), &mut writer)
+                .unwrap();
+        }
+        writer.finish().unwrap();
+
+        assert_eq!(
+            fs::read_to_string(&output).unwrap(),
+            include_str!("../tests/fixtures/report.html")
+        );
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[test]
+    fn test_sqlite_output_accumulates_runs() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("lib.rs");
+        fs::write(&path, "// TODO: first\n// FIXME: second\n").unwrap();
+        let database = dir.path().join("todos.db");
+
+        for _ in 0..2 {
+            let mut writer = output::Format::Sqlite
+                .create(&database, &Default::default())
+                .unwrap();
+            utils::Scanner::default()
+                .process_file(&path, &mut writer)
+                .unwrap();
+            writer.finish().unwrap();
+        }
+
+        let connection = rusqlite::Connection::open(&database).unwrap();
+        let count = |sql: &str| -> i64 { connection.query_row(sql, [], |row| row.get(0)).unwrap() };
+        assert_eq!(count("SELECT COUNT(*) FROM runs"), 2);
+        assert_eq!(count("SELECT COUNT(*) FROM todos"), 4);
+        assert_eq!(
+            count("SELECT COUNT(*) FROM todos WHERE run_id = 2 AND tag = 'FIXME' AND line = 2"),
+            1
+        );
+    }
+
+    #[test]
+    fn test_process_file_into_any_writer() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("lib.rs");
+        fs::write(&path, "// TODO: buffered\n").unwrap();
+
+        let mut csv_writer = Writer::from_writer(Vec::new());
+        utils::process_file(&path, &mut csv_writer).unwrap();
+
+        assert_eq!(
+            String::from_utf8(csv_writer.into_inner().unwrap()).unwrap(),
+            format!("{},1,TODO,,,,,buffered\n", path.display())
+        );
+    }
+
+    #[test]
+    fn test_tsv_output_round_trips_tabs() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("main.py");
+        fs::write(&path, "# TODO: split on\ttabs, commas\n").unwrap();
+        let output = dir.path().join("out.tsv");
+        let options = output::OutputOptions {
+            delimiter: b'\t',
+            ..Default::default()
+        };
+
+        let mut writer = output::Format::Csv
+            .writer(fs::File::create(&output).unwrap(), &options)
+            .unwrap();
+        utils::Scanner::default()
+            .process_file(&path, &mut writer)
+            .unwrap();
+        writer.finish().unwrap();
+
+        let records: Vec<_> = ReaderBuilder::new()
+            .delimiter(b'\t')
+            .from_path(&output)
+            .unwrap()
+            .records()
+            .map(Result::unwrap)
+            .collect();
+        assert_eq!(records.len(), 1);
+        assert_eq!(&records[0][7], "split on\ttabs, commas");
+    }
+
+    #[test]
+    fn test_sarif_output_shape() {
+        #[derive(serde::Deserialize)]
+        struct Log {
+            version: String,
+            runs: Vec<Run>,
+        }
+        #[derive(serde::Deserialize)]
+        struct Run {
+            tool: Tool,
+            results: Vec<SarifResult>,
+        }
+        #[derive(serde::Deserialize)]
+        struct Tool {
+            driver: Driver,
+        }
+        #[derive(serde::Deserialize)]
+        struct Driver {
+            rules: Vec<Rule>,
+        }
+        #[derive(serde::Deserialize)]
+        struct Rule {
+            id: String,
+        }
+        #[derive(serde::Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct SarifResult {
+            rule_id: String,
+            rule_index: usize,
+            level: String,
+            message: Message,
+            locations: Vec<Location>,
+        }
+        #[derive(serde::Deserialize)]
+        struct Message {
+            text: String,
+        }
+        #[derive(serde::Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Location {
+            physical_location: PhysicalLocation,
+        }
+        #[derive(serde::Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct PhysicalLocation {
+            artifact_location: ArtifactLocation,
+            region: Region,
+        }
+        #[derive(serde::Deserialize)]
+        struct ArtifactLocation {
+            uri: String,
+        }
+        #[derive(serde::Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Region {
+            start_line: usize,
+        }
+
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join("src")).unwrap();
+        let path = dir.path().join("src").join("lib.rs");
+        fs::write(&path, "// TODO: first\n// FIXME: second\n// TODO: third\n").unwrap();
+        let output = dir.path().join("out.sarif");
+        let options = output::OutputOptions {
+            root: dir.path().to_str().unwrap().to_string(),
+            sarif_levels: HashMap::from([("FIXME".to_string(), "warning".to_string())]),
+            ..Default::default()
+        };
+
+        let mut writer = output::Format::Sarif
+            .writer(fs::File::create(&output).unwrap(), &options)
+            .unwrap();
+        utils::Scanner::default()
+            .process_file(&path, &mut writer)
+            .unwrap();
+        writer.finish().unwrap();
+
+        let log: Log = serde_json::from_str(&fs::read_to_string(&output).unwrap()).unwrap();
+        assert_eq!(log.version, "2.1.0");
+        let run = &log.runs[0];
+        let rules: Vec<_> = run
+            .tool
+            .driver
+            .rules
+            .iter()
+            .map(|r| r.id.as_str())
+            .collect();
+        assert_eq!(rules, ["TODO", "FIXME"]);
+        let results: Vec<_> = run
+            .results
+            .iter()
+            .map(|r| {
+                let location = &r.locations[0].physical_location;
+                (
+                    r.rule_id.as_str(),
+                    r.rule_index,
+                    r.level.as_str(),
+                    r.message.text.as_str(),
+                    location.artifact_location.uri.as_str(),
+                    location.region.start_line,
+                )
+            })
+            .collect();
+        assert_eq!(
+            results,
+            [
+                ("TODO", 0, "note", "first", "src/lib.rs", 1),
+                ("FIXME", 1, "warning", "second", "src/lib.rs", 2),
+                ("TODO", 0, "note", "third", "src/lib.rs", 3),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_gh_annotations_output() {
+        let workspace = tempfile::tempdir().unwrap();
+        let entry = |file: &Path, line, comment: &str| utils::TodoEntry {
+            file: file.to_str().unwrap().to_string(),
+            line,
+            cell: None,
+            language: None,
+            offset: None,
+            todo: utils::Todo {
+                tag: String::from("TODO"),
+                comment: comment.to_string(),
+                ..Default::default()
+            },
+            raw_line: None,
+            blame_author: None,
+            author_date: None,
+            commit: None,
+            age_days: None,
+            modified: None,
+            context: None,
+            link: None,
+        };
+        let mut buffer = Vec::new();
+        let mut writer = output::GhAnnotationWriter::with_workspace(
+            &mut buffer,
+            output::AnnotationLevel::Notice,
+            workspace.path().to_path_buf(),
+        );
+
+        let file = workspace.path().join("src").join("a,b.rs");
+        writer
+            .write_entry(&entry(&file, 42, "handle retries"))
+            .unwrap();
+        writer
+            .write_entry(&entry(&file, 7, "100% done\nnext:: line"))
+            .unwrap();
+        writer.finish().unwrap();
+
+        assert_eq!(
+            String::from_utf8(buffer).unwrap(),
+            "::notice file=src/a%2Cb.rs,line=42::TODO: handle retries\n\
+             ::notice file=src/a%2Cb.rs,line=7::TODO: 100%25 done%0Anext:: line\n"
+        );
+    }
+
+    #[test]
+    fn test_junit_output_escapes_comments() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("lib.rs");
+        fs::write(
+            &path,
+            "// TODO: check a < b && \"quoted\" 'text'\n// FIXME: second\n",
+        )
+        .unwrap();
+        let output = dir.path().join("junit.xml");
+        let options = output::OutputOptions {
+            root: String::from("src"),
+            ..Default::default()
+        };
+
+        let mut writer = output::Format::Junit
+            .writer(fs::File::create(&output).unwrap(), &options)
+            .unwrap();
+        utils::Scanner::default()
+            .process_file(&path, &mut writer)
+            .unwrap();
+        writer.finish().unwrap();
+
+        let file = path.display();
+        assert_eq!(
+            fs::read_to_string(&output).unwrap(),
+            format!(
+                r#"<?xml version="1.0" encoding="UTF-8"?>
+<testsuites tests="2" failures="2" errors="0">
+  <testsuite name="src" tests="2" failures="2" errors="0" skipped="0">
+    <testcase classname="{file}" name="line 1">
+      <failure message="check a &lt; b &amp;&amp; &quot;quoted&quot; &#39;text&#39;" type="TODO">check a &lt; b &amp;&amp; &quot;quoted&quot; &#39;text&#39;</failure>
+    </testcase>
+    <testcase classname="{file}" name="line 2">
+      <failure message="second" type="FIXME">second</failure>
+    </testcase>
+  </testsuite>
+</testsuites>
+"#
+            )
+        );
+    }
+
+    #[cfg(feature = "yaml")]
+    #[test]
+    fn test_yaml_output_round_trips() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("main.py");
+        fs::write(&path, "# TODO: no\n# TODO: 42\n# TODO: plain text\n").unwrap();
+        let output = dir.path().join("out.yaml");
+
+        let mut writer = output::Format::Yaml
+            .writer(fs::File::create(&output).unwrap(), &Default::default())
+            .unwrap();
+        utils::Scanner::default()
+            .process_file(&path, &mut writer)
+            .unwrap();
+        writer.finish().unwrap();
+
+        let yaml = fs::read_to_string(&output).unwrap();
+        assert!(yaml.starts_with("- file: "), "{}", yaml);
+        assert!(yaml.contains("  comment: '42'\n"), "{}", yaml);
+        let entries: Vec<serde_yaml::Value> = serde_yaml::from_str(&yaml).unwrap();
+        let comments: Vec<_> = entries.iter().map(|e| e["comment"].as_str()).collect();
+        assert_eq!(comments, [Some("no"), Some("42"), Some("plain text")]);
+        assert_eq!(entries[1]["line"].as_u64(), Some(2));
+    }
+
+    #[cfg(feature = "yaml")]
+    #[test]
+    fn test_yaml_output_empty_scan() {
+        let mut buffer = Vec::new();
+        let mut writer = output::YamlWriter::new(&mut buffer);
+        writer.finish().unwrap();
+
+        assert_eq!(String::from_utf8(buffer).unwrap(), "[]\n");
+    }
+
+    #[test]
+    fn test_xml_output_matches_golden_file() {
+        let root = Path::new("tests/fixtures/report");
+        let mut buffer = Vec::new();
+        let mut writer = output::XmlWriter::new(&mut buffer);
+        for file in ["build.py", "src/main.rs"] {
+            utils::Scanner::default()
+                .process_file(&root.join(file), &mut writer)
+                .unwrap();
+        }
+        writer.finish().unwrap();
+
+        assert_eq!(
+            String::from_utf8(buffer).unwrap(),
+            include_str!("../tests/fixtures/todos.xml")
+        );
+    }
+
+    #[cfg(feature = "xlsx")]
+    #[test]
+    fn test_xlsx_output_writes_a_workbook() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("lib.rs");
+        let long_comment = "x".repeat(5000);
+        fs::write(
+            &path,
+            format!("// TODO: short\n// FIXME: {}\n", long_comment),
+        )
+        .unwrap();
+        let output = dir.path().join("todos.xlsx");
+
+        let mut writer = output::Format::Xlsx
+            .create(&output, &Default::default())
+            .unwrap();
+        utils::Scanner::default()
+            .process_file(&path, &mut writer)
+            .unwrap();
+        writer.finish().unwrap();
+
+        let workbook = fs::read(&output).unwrap();
+        assert!(workbook.starts_with(b"PK\x03\x04"));
+    }
+
+    #[cfg(feature = "xlsx")]
+    #[test]
+    fn test_xlsx_output_truncates_overlong_cells() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("lib.rs");
+        fs::write(&path, format!("// TODO: {}\n", "é".repeat(40_000))).unwrap();
+        let output = dir.path().join("todos.xlsx");
+
+        let mut writer = output::Format::Xlsx
+            .create(&output, &Default::default())
+            .unwrap();
+        utils::Scanner::default()
+            .process_file(&path, &mut writer)
+            .unwrap();
+        writer.finish().unwrap();
+
+        let workbook = fs::read(&output).unwrap();
+        assert!(workbook.starts_with(b"PK\x03\x04"));
+    }
+
+    #[test]
+    fn test_grep_output() {
+        let entry = |file: &str, comment: &str| utils::TodoEntry {
+            file: file.to_string(),
+            line: 42,
+            cell: None,
+            language: None,
+            offset: None,
+            todo: utils::Todo {
+                column: 5,
+                tag: String::from("TODO"),
+                comment: comment.to_string(),
+                ..Default::default()
+            },
+            raw_line: None,
+            blame_author: None,
+            author_date: None,
+            commit: None,
+            age_days: None,
+            modified: None,
+            context: None,
+            link: None,
+        };
+        let current_dir = std::env::current_dir().unwrap();
+        let absolute = current_dir.join("src").join("lib.rs");
+        let mut buffer = Vec::new();
+        let mut writer = output::GrepWriter::new(&mut buffer);
+
+        writer
+            .write_entry(&entry("./src/main.rs", "handle retries"))
+            .unwrap();
+        writer
+            .write_entry(&entry(absolute.to_str().unwrap(), "first\nsecond"))
+            .unwrap();
+        writer.finish().unwrap();
+
+        assert_eq!(
+            String::from_utf8(buffer).unwrap(),
+            "src/main.rs:42:5: TODO: handle retries\nsrc/lib.rs:42:5: TODO: first second\n"
+        );
+    }
+
+    #[test]
+    fn test_csv_output_selected_columns() {
+        use output::Column;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("lib.rs");
+        fs::write(&path, "\n// FIXME(bob): reorder\n").unwrap();
+        let scan = |columns: Vec<Column>| {
+            let mut writer = output::CsvWriter::new(Vec::new(), columns);
+            writer.write_header().unwrap();
+            utils::Scanner::default()
+                .process_file(&path, &mut writer)
+                .unwrap();
+            writer.finish().unwrap();
+            writer.into_inner().unwrap()
+        };
+
+        assert_eq!(
+            scan(vec![Column::Line, Column::Author, Column::Comment]),
+            b"Line,Author,Comment\n2,bob,reorder\n"
+        );
+        assert_eq!(
+            scan(vec![Column::Comment, Column::Tag]),
+            b"Comment,Tag\nreorder,FIXME\n"
+        );
+        assert_eq!(Column::from_name("file"), Some(Column::File));
+        assert_eq!(Column::from_name("Overdue"), Some(Column::Overdue));
+        assert_eq!(Column::from_name("path"), None);
+    }
+
+    #[test]
+    fn test_csv_output_header_options() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("lib.rs");
+        fs::write(&path, "// TODO: header\n").unwrap();
+        let scan = |header: output::Header| {
+            let output = dir.path().join("out.csv");
+            let options = output::OutputOptions {
+                columns: vec![
+                    output::Column::File,
+                    output::Column::Line,
+                    output::Column::Comment,
+                ],
+                header,
+                ..Default::default()
+            };
+            let mut writer = output::Format::Csv
+                .writer(fs::File::create(&output).unwrap(), &options)
+                .map_err(|err| err.to_string())?;
+            utils::Scanner::default()
+                .process_file(&path, &mut writer)
+                .unwrap();
+            writer.finish().unwrap();
+            Ok::<_, String>(fs::read_to_string(&output).unwrap())
+        };
+        let row = format!("{},1,header\n", path.display());
 
-    lazy_static! {
-        static ref TODO_PATTERN: Regex =
-            Regex::new(r"(?m)^(?:\s*//|\s*#)\s*TODO:\s*(.*\S)\s*$").unwrap();
+        assert_eq!(scan(output::Header::Omit).unwrap(), row);
+        assert_eq!(
+            scan(output::Header::Custom(vec![
+                "path".to_string(),
+                "lineno".to_string(),
+                "text".to_string(),
+            ]))
+            .unwrap(),
+            format!("path,lineno,text\n{}", row)
+        );
+        assert_eq!(
+            scan(output::Header::Columns).unwrap(),
+            format!("File,Line,Comment\n{}", row)
+        );
+        assert_eq!(
+            scan(output::Header::Custom(vec!["path".to_string()])).unwrap_err(),
+            "the header has 1 names but there are 3 columns"
+        );
     }
 
-    #[derive(Debug, PartialEq, Eq)]
-    enum FileExtension {
-        Rust,
-        Python,
-        Java,
-        TypeScript,
-        JavaScript,
+    #[test]
+    fn test_csv_output_sanitizes_formulas() {
+        let comments = [
+            "=HYPERLINK(\"http://evil\",\"click\")",
+            "+1",
+            "-1",
+            "@SUM(A1)",
+            "\tcmd",
+            "\rcmd",
+            "plain = text",
+        ];
+        let scan = |sanitize: bool| {
+            let mut writer = output::CsvWriter::new(Vec::new(), vec![output::Column::Comment])
+                .sanitize(sanitize);
+            for comment in comments {
+                let entry = utils::TodoEntry {
+                    file: String::from("main.py"),
+                    line: 1,
+                    cell: None,
+                    language: None,
+                    offset: None,
+                    todo: utils::Todo {
+                        comment: comment.to_string(),
+                        ..Default::default()
+                    },
+                    raw_line: None,
+                    blame_author: None,
+                    author_date: None,
+                    commit: None,
+                    age_days: None,
+                    modified: None,
+                    context: None,
+                    link: None,
+                };
+                writer.write_entry(&entry).unwrap();
+            }
+            let output = writer.into_inner().unwrap();
+            ReaderBuilder::new()
+                .has_headers(false)
+                .from_reader(output.as_slice())
+                .records()
+                .map(|record| record.unwrap()[0].to_string())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            scan(true),
+            [
+                "'=HYPERLINK(\"http://evil\",\"click\")",
+                "'+1",
+                "'-1",
+                "'@SUM(A1)",
+                "'\tcmd",
+                "'\rcmd",
+                "plain = text",
+            ]
+        );
+        assert_eq!(scan(false), comments);
     }
 
-    impl FileExtension {
-        fn from_str(ext: &str) -> Option<Self> {
-            match ext {
-                "rs" => Some(Self::Rust),
-                "py" => Some(Self::Python),
-                "java" => Some(Self::Java),
-                "ts" => Some(Self::TypeScript),
-                "js" => Some(Self::JavaScript),
-                _ => None,
-            }
+    #[test]
+    fn test_org_output_matches_golden_file() {
+        let root = Path::new("tests/fixtures/report");
+        let mut buffer = Vec::new();
+        let mut writer = output::OrgWriter::new(&mut buffer, "tests/fixtures/report");
+        for file in ["build.py", "src/main.rs"] {
+            utils::Scanner::default()
+                .process_file(&root.join(file), &mut writer)
+                .unwrap();
         }
+        let starred = utils::TodoEntry {
+            file: String::from("tests/fixtures/report/src/main.rs"),
+            line: 3,
+            cell: None,
+            language: None,
+            offset: None,
+            todo: utils::Todo {
+                tag: String::from("TODO"),
+                comment: String::from("* not a headline"),
+                ..Default::default()
+            },
+            raw_line: None,
+            blame_author: None,
+            author_date: None,
+            commit: None,
+            age_days: None,
+            modified: None,
+            context: None,
+            link: None,
+        };
+        writer.write_entry(&starred).unwrap();
+        writer.finish().unwrap();
+
+        assert_eq!(
+            String::from_utf8(buffer).unwrap(),
+            include_str!("../tests/fixtures/todos.org")
+        );
     }
 
-    pub fn extract_todo_comment(line: &str, todo_pattern: &Regex) -> Option<String> {
-        if let Some(captures) = todo_pattern.captures(line) {
-            Some(
-                captures
-                    .get(1)
-                    .map_or(String::new(), |m| m.as_str().to_string()),
+    #[test]
+    fn test_process_file_inline_comments() {
+        let dir = tempfile::tempdir().unwrap();
+        let rust = dir.path().join("lib.rs");
+        fs::write(
+            &rust,
+            concat!(
+                "let x = 5; // TODO: use a constant\n",
+                "let s = \"// TODO: not real\";\n",
+                "let s = \"// TODO: not real\"; // FIXME: real \"one\"\n",
+                "fn f<'a>(x: &'a str) {} // TODO: lifetimes\n",
+                "let url = \"http://example.com\";\n",
+            ),
+        )
+        .unwrap();
+        let python = dir.path().join("main.py");
+        fs::write(
+            &python,
+            "x = 1  # TODO: trailing\ny = '# TODO: quoted'\ncolor = \"#TODO: no\"\n",
+        )
+        .unwrap();
+        let output = dir.path().join("out.csv");
+        let scanner = utils::ScannerBuilder::new().include_inline(true).build();
+
+        let mut csv_writer = Writer::from_path(&output).unwrap();
+        scanner.process_file(&rust, &mut csv_writer).unwrap();
+        scanner.process_file(&python, &mut csv_writer).unwrap();
+        utils::process_file(&rust, &mut csv_writer).unwrap();
+        csv_writer.flush().unwrap();
+
+        let records: Vec<_> = ReaderBuilder::new()
+            .has_headers(false)
+            .from_path(&output)
+            .unwrap()
+            .records()
+            .map(Result::unwrap)
+            .collect();
+        let rows: Vec<_> = records.iter().map(|r| (&r[1], &r[2], &r[7])).collect();
+        assert_eq!(
+            rows,
+            vec![
+                ("1", "TODO", "use a constant"),
+                ("3", "FIXME", "real \"one\""),
+                ("4", "TODO", "lifetimes"),
+                ("1", "TODO", "trailing"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_process_file_multi_line_block_comments() {
+        let dir = tempfile::tempdir().unwrap();
+        let java = dir.path().join("Cache.java");
+        fs::write(
+            &java,
+            concat!(
+                "/*\n",
+                " * TODO: rework the cache eviction\n",
+                " */\n",
+                "String s = \"/*\"; // not a comment\n",
+                "TODO: not in a comment\n",
+                "/* opening line\n",
+                "   FIXME: without a star */ int x;\n",
+                "TODO: after the block\n",
+            ),
+        )
+        .unwrap();
+        let rust = dir.path().join("lib.rs");
+        fs::write(
+            &rust,
+            concat!(
+                "/* outer\n",
+                "   /* inner */\n",
+                "   TODO: still inside\n",
+                "*/\n",
+                "TODO: outside\n",
+                "/* unterminated\n",
+            ),
+        )
+        .unwrap();
+        let c = dir.path().join("main.c");
+        fs::write(&c, "/* a\n   /* b */\n   TODO: closed by b\n").unwrap();
+
+        let records = process_files(&[&java, &rust, &c, &java], dir.path());
+        let rows: Vec<_> = records.iter().map(|r| (&r[1], &r[2], &r[7])).collect();
+        assert_eq!(
+            rows,
+            vec![
+                ("2", "TODO", "rework the cache eviction"),
+                ("7", "FIXME", "without a star"),
+                ("3", "TODO", "still inside"),
+                ("2", "TODO", "rework the cache eviction"),
+                ("7", "FIXME", "without a star"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_process_file_multiline_continuations() {
+        let dir = tempfile::tempdir().unwrap();
+        let rust = dir.path().join("lib.rs");
+        fs::write(
+            &rust,
+            concat!(
+                "// TODO: split this function\n",
+                "// into smaller parts, see #12\n",
+                "fn f() {}\n",
+                "// TODO: first\n",
+                "// FIXME: second\n",
+                "//\n",
+                "// not part of it\n",
+                "/* TODO: rework the cache\n",
+                " * eviction policy\n",
+                " */\n",
+            ),
+        )
+        .unwrap();
+        let python = dir.path().join("main.py");
+        fs::write(&python, "# TODO: trailing\n#   blank lines\n#\n#\n").unwrap();
+        let output = dir.path().join("out.csv");
+        let scanner = utils::ScannerBuilder::new().multiline(true).build();
+
+        let mut csv_writer = Writer::from_path(&output).unwrap();
+        scanner.process_file(&rust, &mut csv_writer).unwrap();
+        scanner.process_file(&python, &mut csv_writer).unwrap();
+        csv_writer.flush().unwrap();
+
+        let records: Vec<_> = ReaderBuilder::new()
+            .has_headers(false)
+            .from_path(&output)
+            .unwrap()
+            .records()
+            .map(Result::unwrap)
+            .collect();
+        let rows: Vec<_> = records
+            .iter()
+            .map(|r| (&r[1], &r[2], &r[4], &r[7]))
+            .collect();
+        assert_eq!(
+            rows,
+            vec![
+                (
+                    "1",
+                    "TODO",
+                    "#12",
+                    "split this function into smaller parts, see #12"
+                ),
+                ("4", "TODO", "", "first"),
+                ("5", "FIXME", "", "second"),
+                ("8", "TODO", "", "rework the cache eviction policy"),
+                ("1", "TODO", "", "trailing blank lines"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_process_file_python_docstrings() {
+        let dir = tempfile::tempdir().unwrap();
+        let python = dir.path().join("main.py");
+        fs::write(
+            &python,
+            concat!(
+                "def f():\n",
+                "    \"\"\"TODO: add type hints\"\"\"\n",
+                "    '''\n",
+                "    Frobnicates.\n",
+                "\n",
+                "    FIXME(alice): handle bytes\n",
+                "    '''\n",
+                "    x = \"a TODO: not a docstring\"\n",
+                "    \"\"\"Return the TODO list.\"\"\"\n",
+                "    \"\"\"\n",
+                "    TODO list management.\n",
+                "    \"\"\"\n",
+                "    s = '\"\"\"' # \"\"\"\n",
+                "    TODO: not in a docstring\n",
+                "    # TODO: a comment\n",
+                "    r\"\"\"TODO: on the opening line\n",
+                "    more text\"\"\"\n",
+            ),
+        )
+        .unwrap();
+
+        let records = process_files(&[&python], dir.path());
+        let rows: Vec<_> = records
+            .iter()
+            .map(|r| (&r[1], &r[2], &r[3], &r[7]))
+            .collect();
+        assert_eq!(
+            rows,
+            vec![
+                ("2", "TODO", "", "add type hints"),
+                ("6", "FIXME", "alice", "handle bytes"),
+                ("15", "TODO", "", "a comment"),
+                ("16", "TODO", "", "on the opening line"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_process_file_doc_comments() {
+        use output::Column;
+
+        let dir = tempfile::tempdir().unwrap();
+        let rust = dir.path().join("lib.rs");
+        fs::write(
+            &rust,
+            concat!(
+                "//! TODO: crate-level overview\n",
+                "/// TODO: document the error cases\n",
+                "// TODO: plain comment\n",
+            ),
+        )
+        .unwrap();
+        let csharp = dir.path().join("Cache.cs");
+        fs::write(&csharp, "/// FIXME: summary\n").unwrap();
+        let typescript = dir.path().join("index.ts");
+        fs::write(
+            &typescript,
+            "/// <reference path=\"a.d.ts\" />\n/// TODO: typings\n",
+        )
+        .unwrap();
+
+        let mut writer =
+            output::CsvWriter::new(Vec::new(), vec![Column::Line, Column::Doc, Column::Comment]);
+        for path in [&rust, &csharp, &typescript] {
+            utils::Scanner::default()
+                .process_file(path, &mut writer)
+                .unwrap();
+        }
+        writer.finish().unwrap();
+
+        assert_eq!(
+            String::from_utf8(writer.into_inner().unwrap()).unwrap(),
+            concat!(
+                "1,true,crate-level overview\n",
+                "2,true,document the error cases\n",
+                "3,false,plain comment\n",
+                "1,true,summary\n",
+                "2,true,typings\n",
             )
-        } else {
-            None
+        );
+        assert_eq!(Column::from_name("doc"), Some(Column::Doc));
+    }
+
+    #[test]
+    fn test_process_file_inline_skips_string_literals() {
+        let dir = tempfile::tempdir().unwrap();
+        let rust = dir.path().join("lib.rs");
+        fs::write(
+            &rust,
+            concat!(
+                "let s = \"say \\\"// TODO: fake\\\"\";\n",
+                "let s = \"a \\\"quote\\\"\"; // TODO: after an escaped quote\n",
+                "let s = r\"C:\\\"; // TODO: after a raw string\n",
+                "let s = r#\"\"// TODO: fake\"\"#;\n",
+                "let q = '\"'; // TODO: after a char literal\n",
+                "let url = \"https://example.com#TODO:anchor\";\n",
+            ),
+        )
+        .unwrap();
+        let shell = dir.path().join("build.sh");
+        fs::write(
+            &shell,
+            concat!(
+                "s=\"# TODO: fake\"\n",
+                "echo 'C:\\' # TODO: after a single-quoted string\n",
+            ),
+        )
+        .unwrap();
+        let output = dir.path().join("out.csv");
+        let scanner = utils::ScannerBuilder::new().include_inline(true).build();
+
+        let mut csv_writer = Writer::from_path(&output).unwrap();
+        scanner.process_file(&rust, &mut csv_writer).unwrap();
+        scanner.process_file(&shell, &mut csv_writer).unwrap();
+        csv_writer.flush().unwrap();
+
+        let records: Vec<_> = ReaderBuilder::new()
+            .has_headers(false)
+            .from_path(&output)
+            .unwrap()
+            .records()
+            .map(Result::unwrap)
+            .collect();
+        let rows: Vec<_> = records.iter().map(|r| (&r[1], &r[7])).collect();
+        assert_eq!(
+            rows,
+            vec![
+                ("2", "after an escaped quote"),
+                ("3", "after a raw string"),
+                ("5", "after a char literal"),
+                ("2", "after a single-quoted string"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_process_file_reports_character_columns() {
+        use output::Column;
+
+        let dir = tempfile::tempdir().unwrap();
+        let rust = dir.path().join("lib.rs");
+        fs::write(
+            &rust,
+            "\t\t// TODO: indented with tabs\nlet s = \"héllo\"; // FIXME: after ünïcödé\n",
+        )
+        .unwrap();
+        let haskell = dir.path().join("Main.lhs");
+        fs::write(&haskell, "> -- TODO: literate\n").unwrap();
+
+        let mut writer =
+            output::CsvWriter::new(Vec::new(), vec![Column::Line, Column::Column, Column::Tag]);
+        for path in [&rust, &haskell] {
+            utils::ScannerBuilder::new()
+                .include_inline(true)
+                .build()
+                .process_file(path, &mut writer)
+                .unwrap();
         }
+        writer.finish().unwrap();
+
+        assert_eq!(
+            String::from_utf8(writer.into_inner().unwrap()).unwrap(),
+            "1,6,TODO\n2,21,FIXME\n1,6,TODO\n"
+        );
     }
 
-    pub fn is_supported_file(entry: &ignore::DirEntry) -> bool {
-        entry.path().is_file()
-            && entry
-                .path()
-                .extension()
-                .and_then(|ext| ext.to_str().and_then(|s| FileExtension::from_str(s)))
-                .is_some()
+    #[test]
+    fn test_process_file_reports_byte_offsets() {
+        use output::Column;
+
+        let contents =
+            "x = 1\r\n# TODO: after crlf\ny = 2\n  # TODO: after lf\r\ns = \"é\"  # TODO: é\n";
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("mixed.py");
+        fs::write(&path, contents).unwrap();
+        let mut writer = output::CsvWriter::new(Vec::new(), vec![Column::Offset, Column::Line]);
+        utils::ScannerBuilder::new()
+            .include_inline(true)
+            .build()
+            .process_file(&path, &mut writer)
+            .unwrap();
+        writer.finish().unwrap();
+
+        let csv = String::from_utf8(writer.into_inner().unwrap()).unwrap();
+        assert_eq!(csv, "9,2\n36,4\n64,5\n");
+        for line in csv.lines() {
+            let offset: usize = line.split(',').next().unwrap().parse().unwrap();
+            assert!(contents[offset..].starts_with("TODO"));
+        }
     }
 
-    pub fn process_file(path: &Path, csv_writer: &mut Writer<std::fs::File>) -> io::Result<()> {
-        let file = File::open(path)?;
-        let reader = BufReader::new(file);
+    #[test]
+    fn test_process_file_strips_comment_terminators() {
+        let cases = [
+            ("site.css", "/* TODO: drop this   */", "drop this"),
+            ("index.html", "<!-- TODO: fix copy -->", "fix copy"),
+            ("build.ps1", "<# TODO: check input #>", "check input"),
+            ("Cache.java", " * @todo tidy up */", "tidy up"),
+            ("lib.rs", "// TODO: no terminator", "no terminator"),
+            ("main.c", "/* TODO: block */ let y = 1;", "block"),
+            ("Cache.java", " * @todo tidy up */ int x;", "tidy up"),
+            (
+                "lib.rs",
+                "// TODO: map --> and #> too",
+                "map --> and #> too",
+            ),
+        ];
+        for (file_name, line, comment) in cases {
+            let records = scan_fixture(file_name, line);
+            assert_eq!(records.len(), 1, "{}", line);
+            assert_eq!(&records[0][7], comment, "{}", line);
+        }
+    }
 
-        for (line_number, line) in reader.lines().enumerate() {
-            let line = line?;
-            if let Some(todo_comment) = extract_todo_comment(&line, &*TODO_PATTERN) {
-                csv_writer.write_record(&[
-                    path.to_str().unwrap_or_default(),
-                    &(line_number + 1).to_string(),
-                    &todo_comment,
-                ])?;
-            }
+    #[test]
+    fn test_process_file_context_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("lib.rs");
+        fs::write(
+            &path,
+            "// TODO: first\nfn a() {}\n// TODO: second\n// TODO: third\n",
+        )
+        .unwrap();
+        let output = dir.path().join("out.json");
+
+        let mut writer = output::Format::Json
+            .writer(fs::File::create(&output).unwrap(), &Default::default())
+            .unwrap();
+        utils::ScannerBuilder::new()
+            .context(1)
+            .build()
+            .process_file(&path, &mut writer)
+            .unwrap();
+        writer.finish().unwrap();
+
+        let json: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&output).unwrap()).unwrap();
+        let contexts: Vec<_> = json
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|entry| entry["context"].clone())
+            .collect();
+        assert_eq!(
+            contexts,
+            vec![
+                serde_json::json!(["// TODO: first", "fn a() {}"]),
+                serde_json::json!(["fn a() {}", "// TODO: second", "// TODO: third"]),
+                serde_json::json!(["// TODO: second", "// TODO: third"]),
+            ]
+        );
+
+        let scanner = utils::ScannerBuilder::new().context(5).build();
+        let mut writer = output::CsvWriter::new(Vec::new(), scanner.columns());
+        scanner.process_file(&path, &mut writer).unwrap();
+        writer.finish().unwrap();
+        let output = writer.into_inner().unwrap();
+        let records: Vec<_> = ReaderBuilder::new()
+            .has_headers(false)
+            .from_reader(output.as_slice())
+            .records()
+            .map(Result::unwrap)
+            .collect();
+        assert_eq!(records.len(), 3);
+        for record in records {
+            assert_eq!(
+                &record[8],
+                "// TODO: first\nfn a() {}\n// TODO: second\n// TODO: third"
+            );
         }
+    }
+
+    #[test]
+    fn test_process_file_raw_line() {
+        use output::Column;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("lib.rs");
+        fs::write(
+            &path,
+            "fn f() {\r\n    let x = 1; // TODO: keep the indentation\r\n\t// FIXME:  spaced  \r\n}\r\n",
+        )
+        .unwrap();
+        let scanner = utils::ScannerBuilder::new()
+            .include_inline(true)
+            .raw_line(true)
+            .build();
+        assert_eq!(scanner.columns().last(), Some(&Column::RawLine));
 
-        Ok(())
+        let mut writer = output::CsvWriter::new(Vec::new(), vec![Column::Comment, Column::RawLine]);
+        scanner.process_file(&path, &mut writer).unwrap();
+        writer.finish().unwrap();
+
+        assert_eq!(
+            String::from_utf8(writer.into_inner().unwrap()).unwrap(),
+            concat!(
+                "keep the indentation,    let x = 1; // TODO: keep the indentation\n",
+                "spaced,\t// FIXME:  spaced  \n",
+            )
+        );
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::utils;
-    use lazy_static::lazy_static;
-    use regex::Regex;
+    /// A git repository with a `lib.rs` whose first TODO was committed on 2024-05-06 and whose
+    /// second isn't committed.
+    fn blamed_repository() -> (tempfile::TempDir, std::path::PathBuf) {
+        let repo = tempfile::tempdir().unwrap();
+        let git = |args: &[&str]| {
+            let status = std::process::Command::new("git")
+                .current_dir(repo.path())
+                .args([
+                    "-c",
+                    "user.name=Alice",
+                    "-c",
+                    "user.email=alice@example.com",
+                ])
+                .args(args)
+                .env("GIT_AUTHOR_DATE", "2024-05-06T23:30:00-0700")
+                .output()
+                .unwrap()
+                .status;
+            assert!(status.success(), "git {:?}", args);
+        };
+        let path = repo.path().join("lib.rs");
+        fs::write(&path, "// TODO: committed\n").unwrap();
+        git(&["init", "-q"]);
+        git(&["add", "lib.rs"]);
+        git(&["commit", "-q", "-m", "Add lib.rs"]);
+        fs::write(&path, "// TODO: committed\n// TODO: uncommitted\n").unwrap();
+        (repo, path)
+    }
 
-    lazy_static! {
-        static ref TODO_PATTERN: Regex =
-            Regex::new(r"(?m)^(?:\s*//|\s*#)\s*TODO:\s*(.*\S)\s*$").unwrap();
+    #[test]
+    fn test_process_file_git_blame() {
+        use output::Column;
+
+        let (repo, path) = blamed_repository();
+        let head = std::process::Command::new("git")
+            .current_dir(repo.path())
+            .args(["rev-parse", "--short=7", "HEAD"])
+            .output()
+            .unwrap()
+            .stdout;
+        let head = String::from_utf8(head).unwrap();
+        let outside = tempfile::tempdir().unwrap();
+        let untracked = outside.path().join("main.py");
+        fs::write(&untracked, "# TODO: not in git\n").unwrap();
+
+        let scanner = utils::ScannerBuilder::new().git_blame(true).build();
+        let columns = scanner.columns();
+        let blame_columns = [
+            Column::BlameAuthor,
+            Column::AuthorDate,
+            Column::Commit,
+            Column::Comment,
+        ];
+        assert_eq!(columns[columns.len() - 4..], blame_columns);
+        assert!(!utils::Scanner::default()
+            .columns()
+            .contains(&Column::Commit));
+        let mut writer = output::CsvWriter::new(Vec::new(), blame_columns.to_vec());
+        scanner.process_file(&path, &mut writer).unwrap();
+        scanner.process_file(&untracked, &mut writer).unwrap();
+        writer.finish().unwrap();
+
+        // Uncommitted lines get an empty commit rather than git's all-zero hash.
+        assert_eq!(
+            String::from_utf8(writer.into_inner().unwrap()).unwrap(),
+            format!(
+                "Alice,2024-05-06,{},committed\n,,,uncommitted\n,,,not in git\n",
+                head.trim()
+            )
+        );
     }
 
     #[test]
-    fn test_extract_todo_comment() {
-        let line_with_todo = "    // TODO: Implement the new feature";
-        let line_without_todo = "    // This is a regular comment";
-        let line_with_no_comment = "let x = 5;";
+    fn test_process_file_age_days() {
+        use output::Column;
+
+        let (_repo, path) = blamed_repository();
+        let reference = chrono::NaiveDate::from_ymd_opt(2024, 6, 5);
+        let scan = |min_age_days| {
+            let scanner = utils::ScannerBuilder::new()
+                .age_reference(reference)
+                .min_age_days(min_age_days)
+                .build();
+            assert!(scanner.columns().contains(&Column::AgeDays));
+            assert!(!scanner.columns().contains(&Column::BlameAuthor));
+            let mut writer =
+                output::CsvWriter::new(Vec::new(), vec![Column::AgeDays, Column::Comment]);
+            scanner.process_file(&path, &mut writer).unwrap();
+            writer.finish().unwrap();
+            String::from_utf8(writer.into_inner().unwrap()).unwrap()
+        };
+
+        assert_eq!(scan(None), "30,committed\n,uncommitted\n");
+        assert_eq!(scan(Some(30)), "30,committed\n");
+        assert_eq!(scan(Some(31)), "");
+    }
+
+    #[test]
+    fn test_process_file_modified_column() {
+        use output::Column;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("lib.rs");
+        fs::write(&path, "// TODO: first\n// TODO: second\n").unwrap();
+        let time = std::time::UNIX_EPOCH + std::time::Duration::from_millis(1_700_000_000_750);
+        let file = fs::File::options().write(true).open(&path).unwrap();
+        file.set_modified(time).unwrap();
+
+        let mut writer = output::CsvWriter::new(Vec::new(), vec![Column::Line, Column::Modified]);
+        utils::Scanner::default()
+            .process_file(&path, &mut writer)
+            .unwrap();
+        writer.finish().unwrap();
 
         assert_eq!(
-            utils::extract_todo_comment(line_with_todo, &*TODO_PATTERN),
-            Some(String::from("Implement the new feature"))
+            String::from_utf8(writer.into_inner().unwrap()).unwrap(),
+            "1,2023-11-14T22:13:20Z\n2,2023-11-14T22:13:20Z\n"
         );
+        assert_eq!(Column::from_name("modified"), Some(Column::Modified));
+    }
+
+    #[test]
+    fn test_process_file_path_styles() {
+        use output::Column;
+        use utils::PathStyle;
+
+        let repo = tempfile::tempdir().unwrap();
+        fs::create_dir(repo.path().join(".git")).unwrap();
+        let root = repo.path().join("services");
+        fs::create_dir_all(root.join("api")).unwrap();
+        let path = root.join("api").join("main.py");
+        fs::write(&path, "# TODO: paginate\n").unwrap();
+        let scan = |style| {
+            let scanner = utils::ScannerBuilder::new()
+                .path_style(style, &root)
+                .build();
+            let mut writer = output::CsvWriter::new(Vec::new(), vec![Column::File]);
+            scanner.process_file(&path, &mut writer).unwrap();
+            writer.finish().unwrap();
+            String::from_utf8(writer.into_inner().unwrap()).unwrap()
+        };
+        let line = |path: &Path| format!("{}\n", path.to_str().unwrap());
+
+        assert_eq!(scan(PathStyle::AsGiven), line(&path));
         assert_eq!(
-            utils::extract_todo_comment(line_without_todo, &*TODO_PATTERN),
-            None
+            scan(PathStyle::Relative),
+            line(&Path::new("api").join("main.py"))
         );
         assert_eq!(
-            utils::extract_todo_comment(line_with_no_comment, &*TODO_PATTERN),
-            None
+            scan(PathStyle::Absolute),
+            line(&path.canonicalize().unwrap())
+        );
+        assert_eq!(
+            scan(PathStyle::RepoRelative),
+            line(&Path::new("services").join("api").join("main.py"))
+        );
+        assert_eq!(
+            PathStyle::from_name("repo-relative"),
+            Some(PathStyle::RepoRelative)
         );
     }
 
     #[test]
-    fn test_extract_todo_comment_with_whitespace() {
-        let line_with_todo = "    //   TODO:  Improve error handling  ";
-        let expected = Some(String::from("Improve error handling"));
+    fn test_common_root() {
+        let root = |paths: &[&str]| utils::common_root(paths.iter().map(Path::new));
 
+        assert_eq!(root(&["crates/core", "crates/cli"]), Path::new("crates"));
         assert_eq!(
-            utils::extract_todo_comment(line_with_todo, &*TODO_PATTERN),
-            expected
+            root(&["crates/core", "crates/core/nested"]),
+            Path::new("crates/core")
         );
+        assert_eq!(root(&["src"]), Path::new("src"));
+        assert_eq!(root(&["a", "b"]), Path::new(""));
     }
 
     #[test]
-    fn test_extract_todo_comment_multiline() {
-        let line_with_multiline_todo =
-            "    // TODO: Refactor this code\n    // to make it more efficient";
-        let expected = Some(String::from("Refactor this code"));
+    fn test_file_extension_names() {
+        use utils::FileExtension;
+
+        let cases = [
+            ("lib.rs", "Rust"),
+            ("main.py", "Python"),
+            ("Main.java", "Java"),
+            ("index.tsx", "TypeScript"),
+            ("index.mjs", "JavaScript"),
+            ("main.go", "Go"),
+            ("driver.h", "C"),
+            ("main.cpp", "C++"),
+            ("Rakefile.rake", "Ruby"),
+            ("index.php", "PHP"),
+            ("Program.cs", "C#"),
+            ("build.gradle.kts", "Kotlin"),
+            ("App.swift", "Swift"),
+            ("build.sbt", "Scala"),
+            ("deploy.sh", "Shell"),
+            ("init.lua", "Lua"),
+            ("Main.hs", "Haskell"),
+            ("Main.lhs", "Literate Haskell"),
+            ("schema.sql", "SQL"),
+            ("ci.yml", "YAML"),
+            ("Cargo.toml", "TOML"),
+            ("index.htm", "HTML"),
+            ("pom.xml", "XML"),
+            ("site.css", "CSS"),
+            ("site.scss", "SCSS"),
+            ("site.less", "LESS"),
+            ("README.md", "Markdown"),
+            ("Dockerfile.prod", "Dockerfile"),
+            ("GNUmakefile", "Makefile"),
+            ("justfile", "Justfile"),
+            ("script.pl", "Perl"),
+            ("mix.exs", "Elixir"),
+            ("app.erl", "Erlang"),
+            ("main.dart", "Dart"),
+            ("App.vue", "Vue"),
+            ("App.svelte", "Svelte"),
+            ("analysis.R", "R"),
+            ("report.ipynb", "Jupyter notebook"),
+            ("build.ps1", "PowerShell"),
+            ("main.zig", "Zig"),
+            ("core.cljs", "Clojure"),
+            ("init.el", "Emacs Lisp"),
+            ("main.lisp", "Common Lisp"),
+            (".vimrc", "Vim script"),
+            ("paper.tex", "LaTeX"),
+            ("solver.f90", "Fortran"),
+            ("Jenkinsfile", "Groovy"),
+        ];
+        for (file_name, name) in cases {
+            let language = FileExtension::from_path(Path::new(file_name));
+            assert_eq!(
+                language.map(|language| language.name()),
+                Some(name),
+                "{}",
+                file_name
+            );
+        }
+        assert_eq!(FileExtension::from_path(Path::new("notes.txt")), None);
+
+        for language in FileExtension::ALL {
+            assert_eq!(FileExtension::from_name(language.name()), Some(language));
+        }
+        assert_eq!(FileExtension::from_name("c++"), Some(FileExtension::Cpp));
+        assert_eq!(FileExtension::from_name("py"), Some(FileExtension::Python));
+        assert_eq!(FileExtension::from_name("txt"), None);
+    }
+
+    #[test]
+    fn test_process_file_language_column() {
+        use output::Column;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("build.txt");
+        fs::write(&path, "// TODO: forced\n").unwrap();
+        let scanner = utils::Scanner::default();
+        let mut writer =
+            output::CsvWriter::new(Vec::new(), vec![Column::Language, Column::Comment]);
+        scanner
+            .process_file_as(&path, Some(utils::FileExtension::Groovy), &mut writer)
+            .unwrap();
+        scanner.process_file(&path, &mut writer).unwrap();
+        writer.finish().unwrap();
 
         assert_eq!(
-            utils::extract_todo_comment(line_with_multiline_todo, &*TODO_PATTERN),
-            expected
+            String::from_utf8(writer.into_inner().unwrap()).unwrap(),
+            "Groovy,forced\n,forced\n"
         );
     }
 
     #[test]
-    fn test_extract_todo_comment_inline() {
-        let line_with_inline_todo =
-            "let x = 5; // TODO: Use a constant instead of a hardcoded value";
-        let expected = None;
+    fn test_scan_files_stats() {
+        use utils::FileExtension;
+
+        let dir = tempfile::tempdir().unwrap();
+        let files = [
+            ("a.rs", "// TODO: one\n// FIXME: two\n"),
+            ("b.rs", "fn main() {}\n"),
+            ("c.py", "# TODO: three\n"),
+            ("d.txt", "# TODO: four\n"),
+        ];
+        for (name, contents) in files {
+            fs::write(dir.path().join(name), contents).unwrap();
+        }
+        let paths = files.map(|(name, _)| {
+            let path = dir.path().join(name);
+            let language = FileExtension::from_path(&path);
+            (path, language)
+        });
 
+        let mut writer = output::CsvWriter::new(Vec::new(), vec![output::Column::Comment]);
+        let stats = utils::Scanner::default()
+            .scan_files(paths, &mut writer)
+            .unwrap();
+
+        assert_eq!(stats.files_scanned, 4);
+        assert_eq!(stats.files_with_todos, 3);
+        assert_eq!(stats.todos(), 4);
         assert_eq!(
-            utils::extract_todo_comment(line_with_inline_todo, &*TODO_PATTERN),
-            expected
+            stats.todos_by_language,
+            [("Other", 1), ("Python", 1), ("Rust", 2)].into()
         );
+        let json = serde_json::to_value(&stats).unwrap();
+        assert_eq!(json["files_scanned"], 4);
+        assert_eq!(json["todos_by_language"]["Rust"], 2);
+        assert!(json["elapsed_seconds"].is_f64());
     }
 
     #[test]
-    fn test_extract_todo_comment_python() {
-        let line_with_todo = "# TODO: Implement the new feature";
-        let line_without_todo = "# This is a regular comment";
-        let line_with_no_comment = "x = 5";
+    fn test_slash_path() {
+        use std::path::PathBuf;
+
+        let cases = [
+            (r"src\module\file.rs", "src/module/file.rs"),
+            ("src/module/file.rs", "src/module/file.rs"),
+            (r"C:\work\src\lib.rs", "C:/work/src/lib.rs"),
+            (r"\\?\C:\work\src\lib.rs", "C:/work/src/lib.rs"),
+            (r"\\?\UNC\server\share\lib.rs", "//server/share/lib.rs"),
+            (r"\\server\share\lib.rs", "//server/share/lib.rs"),
+        ];
+        for (path, expected) in cases {
+            assert_eq!(
+                utils::slash_path(&PathBuf::from(path)),
+                expected,
+                "{}",
+                path
+            );
+        }
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_process_file_slash_paths() {
+        use output::Column;
+
+        // A Unix file name with backslashes is spelled like a relative Windows path.
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(r"src\api\main.py");
+        fs::write(&path, "# TODO: paginate\n").unwrap();
+        let scan = |slash_paths| {
+            let mut writer = output::CsvWriter::new(Vec::new(), vec![Column::File]);
+            utils::ScannerBuilder::new()
+                .path_style(utils::PathStyle::Relative, dir.path())
+                .slash_paths(slash_paths)
+                .build()
+                .process_file(&path, &mut writer)
+                .unwrap();
+            writer.finish().unwrap();
+            String::from_utf8(writer.into_inner().unwrap()).unwrap()
+        };
 
+        assert_eq!(scan(false), "src\\api\\main.py\n");
+        assert_eq!(scan(true), "src/api/main.py\n");
+    }
+
+    #[test]
+    fn test_link_template() {
+        use utils::LinkTemplate;
+
+        let github = LinkTemplate::parse(
+            "https://github.com/org/repo/blob/{commit}/{path}#L{line}",
+            Some("0123abc"),
+        )
+        .unwrap();
         assert_eq!(
-            utils::extract_todo_comment(line_with_todo, &*TODO_PATTERN),
-            Some(String::from("Implement the new feature"))
+            github.render("src/my module/100%.rs", 42),
+            "https://github.com/org/repo/blob/0123abc/src/my%20module/100%25.rs#L42"
         );
+        let gitlab = LinkTemplate::parse(
+            "https://gitlab.com/org/repo/-/blob/{commit}/{path}#L{line}",
+            Some("main"),
+        )
+        .unwrap();
         assert_eq!(
-            utils::extract_todo_comment(line_without_todo, &*TODO_PATTERN),
-            None
+            gitlab.render("lib/café.rb", 7),
+            "https://gitlab.com/org/repo/-/blob/main/lib/caf%C3%A9.rb#L7"
+        );
+
+        assert!(LinkTemplate::parse("https://example.com/{path}#L{line}", None).is_ok());
+        assert!(LinkTemplate::parse("https://example.com/{commit}/{path}", None).is_err());
+        assert!(LinkTemplate::parse("https://example.com/{branch}/{path}", Some("main")).is_err());
+        assert!(LinkTemplate::parse("https://example.com/{path", Some("main")).is_err());
+    }
+
+    #[test]
+    fn test_process_file_link_column() {
+        use output::Column;
+
+        let (repo, path) = blamed_repository();
+        let head = git::head(repo.path()).unwrap();
+        assert_eq!(head.len(), 40);
+        let subdir = repo.path().join("src");
+        fs::create_dir(&subdir).unwrap();
+        let nested = subdir.join("main.py");
+        fs::write(&nested, "x = 1\n# TODO: nested\n").unwrap();
+
+        let template = utils::LinkTemplate::parse(
+            "https://github.com/org/repo/blob/{commit}/{path}#L{line}",
+            git::head(&subdir).as_deref(),
+        )
+        .unwrap();
+        let scanner = utils::ScannerBuilder::new()
+            .path_style(utils::PathStyle::AsGiven, &subdir)
+            .link_template(Some(template))
+            .build();
+        assert!(scanner.columns().contains(&Column::Link));
+        assert!(!utils::Scanner::default().columns().contains(&Column::Link));
+        let mut writer = output::CsvWriter::new(Vec::new(), vec![Column::Link]);
+        scanner.process_file(&nested, &mut writer).unwrap();
+        scanner.process_file(&path, &mut writer).unwrap();
+        writer.finish().unwrap();
+
+        let url = "https://github.com/org/repo/blob";
+        assert_eq!(
+            String::from_utf8(writer.into_inner().unwrap()).unwrap(),
+            format!(
+                "{url}/{head}/src/main.py#L2\n{url}/{head}/lib.rs#L1\n{url}/{head}/lib.rs#L2\n"
+            )
         );
+    }
+
+    #[test]
+    fn test_extensions() {
+        use output::Column;
+        use utils::{CommentSyntax, Extensions, FileExtension};
+
+        let dir = tempfile::tempdir().unwrap();
+        let proto = dir.path().join("api.proto");
+        fs::write(&proto, "// TODO: version the api\n# not a comment\n").unwrap();
+        let sol = dir.path().join("Token.sol");
+        let dockerfile = dir.path().join("Dockerfile");
+
+        let mut extensions = Extensions::default();
+        assert_eq!(extensions.language(&proto), None);
+        extensions
+            .add("proto", CommentSyntax::Slashes)
+            .add("sol", CommentSyntax::Hash);
+        let language = extensions.language(&proto);
         assert_eq!(
-            utils::extract_todo_comment(line_with_no_comment, &*TODO_PATTERN),
-            None
+            language,
+            Some(FileExtension::Custom(CommentSyntax::Slashes))
+        );
+        assert_eq!(
+            extensions.language(&sol),
+            Some(FileExtension::Custom(CommentSyntax::Hash))
         );
+        assert_eq!(
+            extensions.language(Path::new("lib.rs")),
+            Some(FileExtension::Rust)
+        );
+        assert_eq!(
+            extensions.language(&dockerfile),
+            Some(FileExtension::Dockerfile)
+        );
+
+        let mut writer =
+            output::CsvWriter::new(Vec::new(), vec![Column::Language, Column::Comment]);
+        utils::Scanner::default()
+            .process_file_as(&proto, language, &mut writer)
+            .unwrap();
+        writer.finish().unwrap();
+        assert_eq!(
+            String::from_utf8(writer.into_inner().unwrap()).unwrap(),
+            "Other,version the api\n"
+        );
+
+        extensions.only(["rs", "proto"]).unwrap();
+        assert!(extensions.language(&proto).is_some());
+        assert!(extensions.language(Path::new("lib.rs")).is_some());
+        assert_eq!(extensions.language(&sol), None);
+        assert_eq!(extensions.language(Path::new("main.py")), None);
+        assert_eq!(extensions.language(&dockerfile), None);
+        assert_eq!(extensions.only(["rs", "jsonnet"]).unwrap_err(), "jsonnet");
+        assert_eq!(
+            CommentSyntax::from_name("percent"),
+            Some(CommentSyntax::Percent)
+        );
+        assert_eq!(CommentSyntax::from_name("bang"), None);
     }
 
     #[test]
-    fn test_extract_todo_comment_python_inline() {
-        let line_with_inline_todo = "x = 5  # TODO: Use a constant instead of a hardcoded value";
-        let expected = None;
+    fn test_config() {
+        use utils::{CommentSyntax, PathStyle};
+
+        let dir = tempfile::tempdir().unwrap();
+        let nested = dir.path().join("src").join("bin");
+        fs::create_dir_all(&nested).unwrap();
+        assert_eq!(Config::discover(&nested), None);
 
+        let path = dir.path().join(Config::FILE_NAME);
+        fs::write(
+            &path,
+            "keywords = [\"TODO\", \"HACK\"]\n\
+             exclude = [\"**/generated/**\"]\n\
+             format = \"json\"\n\
+             path-style = \"repo-relative\"\n\
+             \n\
+             [add-extensions]\n\
+             proto = \"slashes\"\n",
+        )
+        .unwrap();
         assert_eq!(
-            utils::extract_todo_comment(line_with_inline_todo, &*TODO_PATTERN),
-            expected
+            Config::discover(&nested),
+            Some(path.canonicalize().unwrap())
+        );
+        let config = Config::load(&path).unwrap();
+        assert_eq!(
+            config.keywords.as_deref(),
+            Some(&["TODO".to_string(), "HACK".to_string()][..])
+        );
+        assert_eq!(config.exclude, ["**/generated/**"]);
+        assert!(config.include.is_empty());
+        assert_eq!(config.extensions, None);
+        assert_eq!(config.add_extensions["proto"], CommentSyntax::Slashes);
+        assert_eq!(config.format, Some(output::Format::Json));
+        assert_eq!(config.path_style, Some(PathStyle::RepoRelative));
+        assert_eq!(Config::load(&nested.join(Config::FILE_NAME)).ok(), None);
+
+        fs::write(&path, "format = \"csv\"\nkeyword = [\"TODO\"]\n").unwrap();
+        let err = Config::load(&path).unwrap_err();
+        assert!(err.starts_with(&path.display().to_string()), "{}", err);
+        assert!(err.contains("line 2"), "{}", err);
+        assert!(err.contains("unknown field `keyword`"), "{}", err);
+
+        fs::write(&path, "format = \"pdf\"\n").unwrap();
+        let err = Config::load(&path).unwrap_err();
+        assert!(err.contains("unknown format `pdf`"), "{}", err);
+    }
+
+    #[test]
+    fn test_append() {
+        use output::{Column, Format, Header, OutputOptions};
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("main.rs");
+        fs::write(&path, "// TODO: first\n").unwrap();
+        let output = dir.path().join("todos.csv");
+        let options = OutputOptions {
+            columns: vec![Column::Line, Column::Comment],
+            append: true,
+            ..Default::default()
+        };
+        let run = |options: &OutputOptions| {
+            let mut writer = Format::Csv.create(&output, options)?;
+            utils::Scanner::default()
+                .process_file(&path, &mut writer)
+                .unwrap();
+            writer.finish()
+        };
+
+        fs::write(&output, "").unwrap();
+        run(&options).unwrap();
+        run(&options).unwrap();
+        assert_eq!(
+            fs::read_to_string(&output).unwrap(),
+            "Line,Comment\n1,first\n1,first\n"
+        );
+
+        let renamed = OutputOptions {
+            header: Header::Custom(vec!["line".to_string(), "text".to_string()]),
+            ..options.clone()
+        };
+        let err = run(&renamed).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+        assert!(err
+            .to_string()
+            .ends_with("has the columns Line,Comment but this run writes line,text"));
+        let more = OutputOptions {
+            columns: vec![Column::File, Column::Line, Column::Comment],
+            ..options.clone()
+        };
+        assert!(run(&more).is_err());
+        let omitted = OutputOptions {
+            header: Header::Omit,
+            ..options.clone()
+        };
+        run(&omitted).unwrap();
+        assert_eq!(fs::read_to_string(&output).unwrap().lines().count(), 4);
+
+        fs::remove_file(&output).unwrap();
+        run(&renamed).unwrap();
+        assert_eq!(fs::read_to_string(&output).unwrap(), "line,text\n1,first\n");
+        assert!(Format::Json.create(&output, &options).is_err());
+    }
+
+    #[test]
+    fn test_sort() {
+        use output::{Column, Sort, SortingWriter};
+
+        let dir = tempfile::tempdir().unwrap();
+        let lower = dir.path().join("a.rs");
+        let upper = dir.path().join("B.rs");
+        fs::write(&lower, "// TODO: zeta\n\n// FIXME: omega\n").unwrap();
+        fs::write(&upper, "\n// TODO: beta\n// TODO: beta\n").unwrap();
+        let sorted = |sort: Sort| {
+            let csv = output::CsvWriter::new(Vec::new(), vec![Column::Line, Column::Comment]);
+            let mut writer = SortingWriter::new(csv, sort);
+            for path in [&lower, &upper] {
+                utils::Scanner::default()
+                    .process_file(path, &mut writer)
+                    .unwrap();
+            }
+            writer.finish().unwrap();
+            String::from_utf8(writer.into_inner().into_inner().unwrap()).unwrap()
+        };
+
+        assert_eq!(sorted(Sort::None), "1,zeta\n3,omega\n2,beta\n3,beta\n");
+        // Paths compare byte by byte, so upper case comes first.
+        assert_eq!(sorted(Sort::Path), "2,beta\n3,beta\n1,zeta\n3,omega\n");
+        assert_eq!(sorted(Sort::Line), "1,zeta\n2,beta\n3,beta\n3,omega\n");
+        assert_eq!(sorted(Sort::Tag), "3,omega\n2,beta\n3,beta\n1,zeta\n");
+        assert_eq!(sorted(Sort::Comment), "2,beta\n3,beta\n3,omega\n1,zeta\n");
+        assert_eq!(Sort::from_name("none"), Some(Sort::None));
+        assert_eq!(Sort::from_name("file"), None);
+    }
+
+    #[test]
+    fn test_group_by_file() {
+        use output::{Column, Format, GroupBy, GroupingWriter, OutputOptions, Sort};
+
+        let dir = tempfile::tempdir().unwrap();
+        let lib = dir.path().join("lib.rs");
+        let main = dir.path().join("main.rs");
+        fs::write(&lib, "// TODO: b\n// FIXME: a\n").unwrap();
+        fs::write(&main, "// TODO: c\n").unwrap();
+        let grouped = |format: Format, columns: Vec<Column>, files: &[&Path]| {
+            let output = dir.path().join("out");
+            let options = OutputOptions {
+                columns,
+                group_by: Some(GroupBy::File),
+                ..Default::default()
+            };
+            let writer = format.create(&output, &options).unwrap();
+            let mut writer = GroupingWriter::new(writer, Sort::Comment);
+            for path in [&main, &lib] {
+                if files.contains(&path.as_path()) {
+                    utils::Scanner::default()
+                        .process_file(path, &mut writer)
+                        .unwrap();
+                }
+            }
+            writer.finish().unwrap();
+            fs::read_to_string(output)
+                .unwrap()
+                .replace(&*dir.path().to_string_lossy(), "")
+        };
+
+        let columns = vec![Column::File, Column::Line, Column::Comment];
+        assert_eq!(
+            grouped(Format::Csv, columns.clone(), &[&lib, &main]),
+            "File,Line,Comment\n\
+             /lib.rs,2,a\n\
+             /lib.rs,1,b\n\
+             /lib.rs,,TOTAL: 2\n\
+             /main.rs,1,c\n\
+             /main.rs,,TOTAL: 1\n\
+             ,,TOTAL: 3\n"
+        );
+        assert_eq!(
+            grouped(Format::Csv, columns, &[]),
+            "File,Line,Comment\n,,TOTAL: 0\n"
         );
+        assert_eq!(
+            grouped(Format::Csv, vec![Column::Tag, Column::File], &[&main]),
+            "Tag,File\nTODO,/main.rs\nTOTAL: 1,/main.rs\nTOTAL: 1,\n"
+        );
+
+        let json: serde_json::Value =
+            serde_json::from_str(&grouped(Format::Json, vec![], &[&lib, &main])).unwrap();
+        let files: Vec<_> = json.as_object().unwrap().keys().cloned().collect();
+        assert_eq!(files, ["/lib.rs", "/main.rs"]);
+        assert_eq!(json["/lib.rs"][0]["comment"], "a");
+        assert_eq!(json["/lib.rs"][1]["comment"], "b");
+        assert_eq!(json["/main.rs"].as_array().unwrap().len(), 1);
+        assert_eq!(grouped(Format::Json, vec![], &[]), "{}\n");
+
+        let options = OutputOptions {
+            group_by: Some(GroupBy::File),
+            ..Default::default()
+        };
+        assert!(Format::Markdown.writer(Vec::new(), &options).is_err());
     }
 }
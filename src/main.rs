@@ -1,39 +1,852 @@
-use csv::Writer;
+use chrono::{Local, NaiveDate};
+use clap::error::ErrorKind;
+use clap::{CommandFactory, Parser};
+use ignore::overrides::{Override, OverrideBuilder};
 use ignore::WalkBuilder;
-use std::env;
+use indicatif::{ProgressBar, ProgressDrawTarget, ProgressStyle};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
 use std::error::Error;
+use std::fs;
+use std::io::{self, IsTerminal, Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use todo::config::Config;
+use todo::git;
+use todo::output::{
+    AnnotationLevel, Column, CountWriter, Format, GroupBy, GroupingWriter, Header, OutputOptions,
+    Sort, SortingWriter, TodoWriter,
+};
+use todo::utils::{
+    CommentSyntax, Extensions, FileExtension, LinkTemplate, PathStyle, ScanStats, ScannerBuilder,
+    TodoEntry,
+};
 
-fn main() -> Result<(), Box<dyn Error>> {
-    let args: Vec<String> = env::args().collect();
+/// Extracts TODO comments from a codebase and saves them into a CSV or another format.
+#[derive(Debug, Parser)]
+#[command(
+    name = "todo_finder",
+    version,
+    override_usage = "todo_finder [OPTIONS] <PATH>... <output.csv|->\n       \
+                      todo_finder [OPTIONS] --output <output.csv|-> <PATH>...\n       \
+                      todo_finder [OPTIONS] --count|--count-by file <PATH>...",
+    after_help = "Formats: csv, json, jsonl, markdown, html, sarif, gh-annotations, junit, yaml, \
+                  xml, grep, org, xlsx, sqlite"
+)]
+struct Cli {
+    /// The directories and files to scan, followed by the output unless --output is given.
+    #[arg(
+        value_name = "PATH",
+        required_unless_present_any = ["files_from", "files_from0"]
+    )]
+    paths: Vec<String>,
+    /// The file to write, or `-` for stdout.
+    #[arg(short, long, value_name = "output.csv|-")]
+    output: Option<String>,
 
-    if args.len() != 3 {
-        eprintln!("Usage: todo_finder <directory> <output.csv>");
-        std::process::exit(1);
+    /// Scan for these keywords instead of the default ones, matched literally.
+    #[arg(long, value_name = "TODO,FIXME,...")]
+    tags: Option<String>,
+    /// Match keywords in any case.
+    #[arg(long)]
+    ignore_case: bool,
+    /// Also report comments after code.
+    #[arg(long)]
+    include_inline: bool,
+    /// Append the comment lines that follow a TODO to its comment.
+    #[arg(long)]
+    multiline: bool,
+    /// Add a Context column with this many lines around each TODO.
+    #[arg(long, value_name = "lines", default_value_t = 0)]
+    context: usize,
+    /// Add a RawLine column with the TODO's line as it appears in the file.
+    #[arg(long)]
+    raw_line: bool,
+    /// Add BlameAuthor, AuthorDate and Commit columns from `git blame`.
+    #[arg(long)]
+    git_blame: bool,
+    /// Add an AgeDays column with the days since the TODO's line was committed.
+    #[arg(long)]
+    age_days: bool,
+    /// Report only the TODOs that are at least this many days old.
+    #[arg(long, value_name = "days")]
+    min_age_days: Option<i64>,
+    /// Add an Overdue column for TODOs due before this date.
+    #[arg(long, value_name = "YYYY-MM-DD|today", value_parser = parse_date)]
+    overdue: Option<NaiveDate>,
+    /// How to spell the paths in the File column [default: as-given].
+    #[arg(
+        long,
+        value_name = "as-given|relative|absolute|repo-relative",
+        value_parser = |name: &str| PathStyle::from_name(name).ok_or("unknown path style")
+    )]
+    path_style: Option<PathStyle>,
+    /// Write the paths with forward slashes.
+    #[arg(long)]
+    slash_paths: bool,
+    /// Add a Link column from a URL with {path}, {line} and {commit} placeholders.
+    #[arg(long, value_name = "url")]
+    link_template: Option<String>,
+    /// The branch {commit} stands for without --git-blame.
+    #[arg(long, value_name = "branch")]
+    link_ref: Option<String>,
+    /// The output format [default: csv].
+    #[arg(
+        long,
+        value_name = "format",
+        value_parser = |name: &str| Format::from_name(name).ok_or("unknown format")
+    )]
+    format: Option<Format>,
+    /// The CSV field separator: `,`, `;`, `|` or a tab.
+    #[arg(long, value_name = "char", default_value = ",", value_parser = parse_delimiter)]
+    delimiter: u8,
+    /// The level of `gh-annotations` workflow commands.
+    #[arg(
+        long,
+        value_name = "warning|notice",
+        default_value = "warning",
+        value_parser = |name: &str| AnnotationLevel::from_name(name).ok_or("unknown level")
+    )]
+    annotation_level: AnnotationLevel,
+    /// The columns to write, in order.
+    // Spelled out so that clap takes the list as one value rather than as repeated values.
+    #[arg(long, value_name = "file,line,...", value_parser = parse_columns)]
+    columns: Option<std::vec::Vec<Column>>,
+    /// Leave out the header row.
+    #[arg(long, conflicts_with = "header")]
+    no_header: bool,
+    /// Rename the columns in the header row.
+    #[arg(long, value_name = "name,name,...")]
+    header: Option<String>,
+    /// The order of the entries: by path and line, by line, tag or comment, or `none` to write
+    /// them in the order they are found, without holding them in memory. Only `none` streams
+    /// formats such as jsonl.
+    #[arg(
+        long,
+        value_name = "path|line|tag|comment|none",
+        default_value = "path",
+        value_parser = |name: &str| Sort::from_name(name).ok_or("unknown sort order")
+    )]
+    sort: Sort,
+    /// Write each file's entries together, in CSV followed by a `TOTAL: n` row, in JSON as an
+    /// object of arrays and in HTML as a section per file, with a grand total row at the end of
+    /// a CSV.
+    #[arg(
+        long,
+        value_name = "file",
+        value_parser = |name: &str| GroupBy::from_name(name).ok_or("unknown grouping")
+    )]
+    group_by: Option<GroupBy>,
+    /// Print the number of TODOs to stdout instead of writing them.
+    #[arg(long, conflicts_with_all = ["output", "format", "group_by", "append", "count_by"])]
+    count: bool,
+    /// Print a `count<TAB>path` line per file with TODOs to stdout instead of writing them, the
+    /// file with the most first.
+    #[arg(
+        long,
+        value_name = "file",
+        value_parser = |name: &str| GroupBy::from_name(name).ok_or("unknown grouping"),
+        conflicts_with_all = ["output", "format", "group_by", "append"]
+    )]
+    count_by: Option<GroupBy>,
+    /// Don't guard cells against spreadsheet formulas.
+    #[arg(long)]
+    no_sanitize: bool,
+    /// Add to the CSV output file instead of replacing it. The header is only written to a new
+    /// or empty file, and must match the existing one otherwise.
+    #[arg(long)]
+    append: bool,
+    /// Don't respect ignore files: `.gitignore`, `.ignore` and git's global and repository
+    /// excludes.
+    #[arg(long)]
+    no_ignore: bool,
+    /// Don't respect git's ignore files, but still respect `.ignore` files.
+    #[arg(long)]
+    no_ignore_vcs: bool,
+    /// Scan hidden files and directories too.
+    #[arg(long)]
+    hidden: bool,
+    /// Follow symbolic links, warning about those that loop back to a parent directory.
+    #[arg(long, visible_alias = "follow-symlinks")]
+    follow: bool,
+    /// Descend at most this many directories below each directory given: 0 scans nothing
+    /// and 1 only the files directly in it.
+    #[arg(long, value_name = "n")]
+    max_depth: Option<usize>,
+    /// Scan only the files with these extensions.
+    #[arg(long, value_name = "rs,go,...")]
+    extensions: Option<String>,
+    /// Also scan the files with these extensions, for comments in the given syntax: slashes,
+    /// hash, dashes, semicolon, percent or html.
+    // Spelled out so that clap takes the list as one value rather than as repeated values.
+    #[arg(long, value_name = "ext=syntax,...", value_parser = parse_added_extensions)]
+    add_extensions: Option<std::vec::Vec<(String, CommentSyntax)>>,
+    /// Scan the files given as paths in this language, whatever their extension.
+    #[arg(
+        long,
+        value_name = "language",
+        value_parser = |name: &str| FileExtension::from_name(name).ok_or("unknown language")
+    )]
+    force_language: Option<FileExtension>,
+    /// Skip the paths under a directory that match this gitignore-style glob, such as
+    /// `**/generated/**` or `*.pb.rs`. Can be repeated.
+    #[arg(long, value_name = "glob")]
+    exclude: Vec<String>,
+    /// Scan only the files under a directory that match this glob, such as `src/**`, unless
+    /// they are excluded. Can be repeated.
+    #[arg(long, value_name = "glob")]
+    include: Vec<String>,
+    /// Scan the newline-separated paths read from this file, or `-` for stdin, instead of
+    /// walking directories.
+    #[arg(long, value_name = "path|-", conflicts_with = "files_from0")]
+    files_from: Option<String>,
+    /// Like --files-from, with NUL-separated paths.
+    #[arg(long, value_name = "path|-")]
+    files_from0: Option<String>,
+    /// Also scan the listed files in no supported language, for `//` and `#` comments.
+    #[arg(long)]
+    no_filter: bool,
+    /// Also write the scan statistics as JSON.
+    #[arg(long, value_name = "path")]
+    stats_json: Option<PathBuf>,
+    /// Exit with status 3 if any TODOs were written, for failing CI checks, like --max-todos 0.
+    /// Errors exit with 1.
+    #[arg(long)]
+    fail_if_found: bool,
+    /// Exit with status 3 if more than this many TODOs were written.
+    #[arg(long, value_name = "n")]
+    max_todos: Option<usize>,
+    /// Read the settings from this file instead of the todo2csv.toml found in the scan root or
+    /// a directory above it.
+    #[arg(long, value_name = "path", conflicts_with = "no_config")]
+    config: Option<PathBuf>,
+    /// Don't read a todo2csv.toml.
+    #[arg(long)]
+    no_config: bool,
+    /// Print nothing but errors.
+    #[arg(short, long, conflicts_with = "verbose")]
+    quiet: bool,
+    /// Also print each file scanned with its number of TODOs, and the files skipped.
+    #[arg(short, long)]
+    verbose: bool,
+    /// How to show the progress of the scan when stderr is a terminal: a spinner, or a bar,
+    /// which lists the files before scanning them.
+    #[arg(
+        long,
+        value_name = "spinner|bar",
+        default_value = "spinner",
+        value_parser = |name: &str| Progress::from_name(name).ok_or("unknown progress style")
+    )]
+    progress: Progress,
+    /// Don't show the progress of the scan.
+    #[arg(long, conflicts_with = "progress")]
+    no_progress: bool,
+}
+
+/// The supported `--progress` values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Progress {
+    Spinner,
+    Bar,
+}
+
+impl Progress {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "spinner" => Some(Self::Spinner),
+            "bar" => Some(Self::Bar),
+            _ => None,
+        }
     }
+}
 
-    let directory = &args[1];
-    let output_file = &args[2];
+impl Cli {
+    /// The source of the file list and the byte that separates its paths, with --files-from.
+    fn file_list(&self) -> Option<(&str, u8)> {
+        match (&self.files_from, &self.files_from0) {
+            (Some(source), _) => Some((source, b'\n')),
+            (_, Some(source)) => Some((source, b'\0')),
+            (None, None) => None,
+        }
+    }
 
-    let mut csv_writer = Writer::from_path(output_file)?;
-    csv_writer.write_record(&["File", "Line", "Comment"])?;
+    /// The most TODOs that can be written without failing the run, with --fail-if-found or
+    /// --max-todos.
+    fn budget(&self) -> Option<usize> {
+        let found = self.fail_if_found.then_some(0);
+        found.into_iter().chain(self.max_todos).min()
+    }
 
-    let walker = WalkBuilder::new(directory)
-        .ignore(true)
-        .git_ignore(true)
-        .git_global(true)
-        .git_exclude(true)
-        .build();
+    /// Fills in the settings from `config` that weren't given on the command line, and returns
+    /// its keyword aliases.
+    fn apply(&mut self, config: Config) -> HashMap<String, String> {
+        if self.tags.is_none() {
+            self.tags = config.keywords.map(|keywords| keywords.join(","));
+        }
+        if self.exclude.is_empty() {
+            self.exclude = config.exclude;
+        }
+        if self.include.is_empty() {
+            self.include = config.include;
+        }
+        if self.extensions.is_none() {
+            self.extensions = config.extensions.map(|exts| exts.join(","));
+        }
+        // Added extensions are merged, with the command line winning for the same extension.
+        if !config.add_extensions.is_empty() {
+            let mut added: Vec<_> = config.add_extensions.into_iter().collect();
+            added.extend(self.add_extensions.take().into_iter().flatten());
+            self.add_extensions = Some(added);
+        }
+        self.format = self.format.or(config.format);
+        self.path_style = self.path_style.or(config.path_style);
+        config.aliases
+    }
+}
 
-    for entry in walker
-        .filter_map(|e| e.ok())
-        .filter(todo::utils::is_supported_file)
+fn main() -> Result<(), Box<dyn Error>> {
+    let mut cli = Cli::parse();
+    // Hidden until the scan starts, and cleared from the terminal whenever a line is logged.
+    let progress = ProgressBar::hidden();
+    init_logger(&cli, &progress);
+    let listed = cli.file_list().is_some();
+    let counting = cli.count || cli.count_by.is_some();
+    let inferred = cli.output.is_none() && !counting;
+    let output_file = match cli.output.take() {
+        Some(output) => output,
+        // Counts go to stdout, so every path is one to scan.
+        None if counting => "-".to_string(),
+        None if cli.paths.len() > 1 || listed && cli.paths.len() == 1 => cli.paths.pop().unwrap(),
+        None => Cli::command()
+            .error(
+                ErrorKind::MissingRequiredArgument,
+                "the following required arguments were not provided:\n  <output.csv|->",
+            )
+            .exit(),
+    };
+    if listed && !cli.paths.is_empty() {
+        Cli::command()
+            .error(
+                ErrorKind::ArgumentConflict,
+                "paths to scan can't be given with --files-from",
+            )
+            .exit();
+    }
+    let output_file = &output_file;
+    // Paths relative to a root, links and report headers start from the first path, or from
+    // the current directory for a file list.
+    let directory = &cli
+        .paths
+        .first()
+        .map_or(".".to_string(), |path| root_directory(path));
+    // With several paths, relative paths start from the directory they share.
+    let other_roots: Vec<_> = cli
+        .paths
+        .iter()
+        .skip(1)
+        .map(|path| root_directory(path))
+        .collect();
+    let config = match &cli.config {
+        _ if cli.no_config => None,
+        Some(path) => Some(path.clone()),
+        None => Config::discover(Path::new(directory)),
+    };
+    let aliases = match config.map(|path| Config::load(&path)).transpose() {
+        Ok(config) => cli.apply(config.unwrap_or_default()),
+        Err(err) => {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        }
+    };
+    // An existing source file taken as the output is more likely a path to scan typed last.
+    let format = cli.format.unwrap_or_default();
+    if inferred
+        && (is_source_file(Path::new(output_file), format) || cli.paths.contains(output_file))
     {
-        todo::utils::process_file(entry.path(), &mut csv_writer)?;
+        Cli::command()
+            .error(
+                ErrorKind::ArgumentConflict,
+                format!(
+                    "the output {} is a file to scan; give it with --output to overwrite it",
+                    output_file
+                ),
+            )
+            .exit();
+    }
+    if let Err(err) = overrides(Path::new("."), &cli) {
+        Cli::command().error(ErrorKind::ValueValidation, err).exit();
+    }
+    let mut extensions = Extensions::default();
+    for (ext, syntax) in cli.add_extensions.iter().flatten() {
+        extensions.add(ext, *syntax);
+    }
+    if let Some(exts) = &cli.extensions {
+        let exts = exts.split(',').map(str::trim).filter(|ext| !ext.is_empty());
+        if let Err(ext) = extensions.only(exts) {
+            let message = format!(
+                "unknown extension \"{}\" in --extensions; give its comment syntax with \
+                 --add-extensions {}=<syntax>",
+                ext, ext
+            );
+            Cli::command()
+                .error(ErrorKind::ValueValidation, message)
+                .exit();
+        }
+    }
+    if let Some(missing) = cli.paths.iter().find(|path| !Path::new(path).exists()) {
+        eprintln!("No such file or directory: {}", missing);
+        std::process::exit(1);
     }
 
-    csv_writer.flush()?;
+    let mut scanner = ScannerBuilder::new();
+    if let Some(tags) = &cli.tags {
+        scanner.keywords(tags.split(',').map(str::trim).filter(|t| !t.is_empty()));
+    }
+    scanner
+        .ignore_case(cli.ignore_case)
+        .include_inline(cli.include_inline)
+        .multiline(cli.multiline)
+        .context(cli.context)
+        .git_blame(cli.git_blame)
+        .reference_date(cli.overdue)
+        .slash_paths(cli.slash_paths)
+        .aliases(aliases);
+    if cli.age_days || cli.min_age_days.is_some() {
+        scanner.age_reference(Some(Local::now().date_naive()));
+        scanner.min_age_days(cli.min_age_days);
+    }
+    let raw_line_column = cli
+        .columns
+        .as_ref()
+        .is_some_and(|columns| columns.contains(&Column::RawLine));
+    scanner.raw_line(cli.raw_line || raw_line_column);
+    scanner
+        .path_style(cli.path_style.unwrap_or_default(), directory)
+        .other_roots(other_roots.iter().map(PathBuf::from));
+    if let Some(template) = &cli.link_template {
+        // Link to the commit that was scanned, when git is in use, rather than a moving branch.
+        let head = cli
+            .git_blame
+            .then(|| git::head(Path::new(directory)))
+            .flatten();
+        match LinkTemplate::parse(template, head.as_deref().or(cli.link_ref.as_deref())) {
+            Ok(template) => scanner.link_template(Some(template)),
+            Err(err) => {
+                eprintln!("{}", err);
+                std::process::exit(1);
+            }
+        };
+    }
+    let scanner = scanner.build();
+
+    let header = Header::from_flags(cli.no_header, cli.header.as_deref());
+    let options = OutputOptions {
+        columns: cli.columns.clone().unwrap_or_else(|| scanner.columns()),
+        root: directory.clone(),
+        other_roots,
+        delimiter: cli.delimiter,
+        annotation_level: cli.annotation_level,
+        header,
+        sanitize: !cli.no_sanitize,
+        append: cli.append,
+        group_by: cli.group_by,
+        ..Default::default()
+    };
+    let writer: Box<dyn TodoWriter> = if counting {
+        Box::new(CountWriter::new(io::stdout().lock(), cli.count_by))
+    } else {
+        let writer = match format.create(Path::new(output_file), &options) {
+            Ok(writer) => writer,
+            Err(err) => {
+                eprintln!("error: {}", err);
+                std::process::exit(1);
+            }
+        };
+        match (cli.group_by, cli.sort) {
+            (Some(_), sort) => Box::new(GroupingWriter::new(writer, sort)),
+            (None, Sort::None) => writer,
+            (None, sort) => Box::new(SortingWriter::new(writer, sort)),
+        }
+    };
+
+    let files = input_files(&cli, &extensions)?;
+    // Files given more than once, or under more than one of the directories, are scanned once.
+    let mut seen = HashSet::new();
+    let files =
+        files.filter(|(path, _)| seen.insert(path.canonicalize().unwrap_or_else(|_| path.clone())));
+    let show_progress = !cli.quiet && !cli.no_progress && io::stderr().is_terminal();
+    let files: Box<dyn Iterator<Item = ScanInput>> = if show_progress {
+        start_progress(&progress, cli.progress);
+        if cli.progress == Progress::Bar {
+            let files: Vec<_> = files.collect();
+            progress.set_length(files.len() as u64);
+            Box::new(files.into_iter())
+        } else {
+            Box::new(files)
+        }
+    } else {
+        Box::new(files)
+    };
+    let files = files.inspect(|(path, _)| {
+        progress.set_message(path.display().to_string());
+        progress.inc(1);
+    });
+    let mut writer = ProgressWriter {
+        inner: writer,
+        progress: progress.clone(),
+        todos: 0,
+    };
+    // Finish the output even if a file fails to scan, so JSON arrays are still closed.
+    let scanned = scanner.scan_files(files, &mut writer);
+    progress.finish_and_clear();
+    let finished = writer.finish();
+    let stats = match scanned.and_then(|stats| finished.map(|_| stats)) {
+        // The reader went away, as with `todo_finder src - | head`.
+        Err(err) if err.kind() == io::ErrorKind::BrokenPipe => return Ok(()),
+        result => result?,
+    };
 
-    println!("Results saved to: {}", output_file);
+    if !cli.quiet && !counting {
+        if output_file != "-" {
+            println!("Results saved to: {}", output_file);
+        }
+        print_stats(&stats, cli.budget());
+    }
+    if let Some(path) = &cli.stats_json {
+        let budget = cli.budget();
+        let stats = StatsJson {
+            stats: &stats,
+            budget,
+            over_budget: budget.is_some_and(|budget| stats.todos() > budget),
+        };
+        fs::write(path, serde_json::to_string_pretty(&stats)? + "\n")?;
+    }
+    if let Some(budget) = cli.budget().filter(|&budget| stats.todos() > budget) {
+        eprintln!("Found {} TODOs (budget {})", stats.todos(), budget);
+        std::process::exit(3);
+    }
 
     Ok(())
 }
+
+/// The files to scan, each with its language, or with `None` for the default comment styles.
+fn input_files<'a>(
+    cli: &'a Cli,
+    extensions: &'a Extensions,
+) -> io::Result<Box<dyn Iterator<Item = ScanInput> + 'a>> {
+    let force_language = cli.force_language;
+    if let Some((source, separator)) = cli.file_list() {
+        let filter = !cli.no_filter;
+        let paths = read_file_list(source, separator)?;
+        return Ok(Box::new(paths.into_iter().filter_map(move |path| {
+            if !path.is_file() {
+                log::warn!("skipping {}, which doesn't exist", path.display());
+                return None;
+            }
+            let language = force_language.or_else(|| extensions.language(&path));
+            if language.is_none() && filter {
+                log::info!("skipping {}: no supported language", path.display());
+                return None;
+            }
+            Some((path, language))
+        })));
+    }
+    Ok(Box::new(cli.paths.iter().flat_map(
+        move |path| -> Box<dyn Iterator<Item = ScanInput>> {
+            let path = Path::new(path);
+            if !path.is_dir() {
+                let language = force_language.or_else(|| extensions.language(path));
+                if language.is_none() {
+                    log::warn!(
+                        "skipping {}, which is in no supported language \
+                         (--force-language scans it)",
+                        path.display()
+                    );
+                }
+                let input = language.map(|language| (path.to_path_buf(), Some(language)));
+                return Box::new(input.into_iter());
+            }
+            // The globs were checked up front, so they also build for this root.
+            let overrides = overrides(path, cli).unwrap_or_else(|_| Override::empty());
+            let vcs = !cli.no_ignore && !cli.no_ignore_vcs;
+            let walker = WalkBuilder::new(path)
+                .overrides(overrides)
+                .hidden(!cli.hidden)
+                .follow_links(cli.follow)
+                .max_depth(cli.max_depth)
+                .parents(!cli.no_ignore)
+                .ignore(!cli.no_ignore)
+                .git_ignore(vcs)
+                .git_global(vcs)
+                .git_exclude(vcs)
+                .build();
+            Box::new(
+                walker
+                    .filter_map(|entry| match entry {
+                        Ok(entry) => Some(entry),
+                        Err(err) => {
+                            if is_loop(&err) {
+                                log::warn!("{}", err);
+                            }
+                            None
+                        }
+                    })
+                    .filter(|entry| entry.path().is_file())
+                    .filter_map(|entry| {
+                        let Some(language) = extensions.language(entry.path()) else {
+                            log::info!(
+                                "skipping {}: no supported language",
+                                entry.path().display()
+                            );
+                            return None;
+                        };
+                        Some((entry.into_path(), Some(language)))
+                    }),
+            )
+        },
+    )))
+}
+
+/// Whether `err` is a symbolic link that loops back to a directory being walked.
+fn is_loop(err: &ignore::Error) -> bool {
+    match err {
+        ignore::Error::Loop { .. } => true,
+        ignore::Error::WithPath { err, .. }
+        | ignore::Error::WithDepth { err, .. }
+        | ignore::Error::WithLineNumber { err, .. } => is_loop(err),
+        _ => false,
+    }
+}
+
+/// The overrides that keep the paths under `root` matching the `--include` globs, if any, and
+/// leave out those matching the `--exclude` globs. The excludes come last, so they win.
+fn overrides(root: &Path, cli: &Cli) -> Result<Override, String> {
+    let invalid = |flag: &str, glob: &str, err: ignore::Error| match err {
+        ignore::Error::Glob { err, .. } => format!("invalid {} glob '{}': {}", flag, glob, err),
+        err => err.to_string(),
+    };
+    let mut builder = OverrideBuilder::new(root);
+    for glob in &cli.include {
+        builder
+            .add(glob)
+            .map_err(|err| invalid("--include", glob, err))?;
+    }
+    for glob in &cli.exclude {
+        builder
+            .add(&format!("!{}", glob))
+            .map_err(|err| invalid("--exclude", glob, err))?;
+    }
+    builder.build().map_err(|err| err.to_string())
+}
+
+/// A file to scan and its language.
+type ScanInput = (PathBuf, Option<FileExtension>);
+
+/// Reads the paths in `source`, a file or `-` for stdin, separated by `separator`. Blank entries
+/// and the `\r` of `\r\n` line endings are dropped.
+fn read_file_list(source: &str, separator: u8) -> io::Result<Vec<PathBuf>> {
+    let list = if source == "-" {
+        let mut list = Vec::new();
+        io::stdin().read_to_end(&mut list)?;
+        list
+    } else {
+        fs::read(source)?
+    };
+    Ok(list
+        .split(|&byte| byte == separator)
+        .map(|path| match separator {
+            b'\n' => path.strip_suffix(b"\r").unwrap_or(path),
+            _ => path,
+        })
+        .filter(|path| !path.is_empty())
+        .map(|path| PathBuf::from(String::from_utf8_lossy(path).into_owned()))
+        .collect())
+}
+
+/// Whether `path` is an existing file in a supported language that `format` doesn't write, such
+/// as `keep.rs` for CSV output, rather than an earlier report such as `todos.md`.
+fn is_source_file(path: &Path, format: Format) -> bool {
+    let reports: &[&str] = match format {
+        Format::Markdown => &["md"],
+        Format::Html => &["html", "htm"],
+        Format::Junit | Format::Xml => &["xml"],
+        #[cfg(feature = "yaml")]
+        Format::Yaml => &["yml", "yaml"],
+        _ => &[],
+    };
+    let extension = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .unwrap_or_default();
+    path.is_file() && FileExtension::from_path(path).is_some() && !reports.contains(&extension)
+}
+
+/// The directory that an input `path` is scanned from: the path itself for a directory, and its
+/// parent for a file.
+fn root_directory(path: &str) -> String {
+    if Path::new(path).is_dir() {
+        return path.to_string();
+    }
+    match Path::new(path).parent().and_then(Path::to_str) {
+        Some("") | None => ".".to_string(),
+        Some(parent) => parent.to_string(),
+    }
+}
+
+/// Logs warnings to stderr, and with --verbose the progress of the scan; --quiet leaves only
+/// errors. The dependencies' own logging stays off.
+fn init_logger(cli: &Cli, progress: &ProgressBar) {
+    let level = if cli.quiet {
+        log::LevelFilter::Error
+    } else if cli.verbose {
+        log::LevelFilter::Info
+    } else {
+        log::LevelFilter::Warn
+    };
+    env_logger::Builder::new()
+        .filter_level(log::LevelFilter::Off)
+        .filter_module("todo", level)
+        .format(|buf, record| match record.level() {
+            log::Level::Error => writeln!(buf, "Error: {}", record.args()),
+            log::Level::Warn => writeln!(buf, "Warning: {}", record.args()),
+            _ => writeln!(buf, "{}", record.args()),
+        })
+        .target(env_logger::Target::Pipe(Box::new(AboveProgress(
+            progress.clone(),
+        ))))
+        .init();
+}
+
+/// Writes to stderr with the progress bar cleared, so the two don't interleave.
+struct AboveProgress(ProgressBar);
+
+impl Write for AboveProgress {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.suspend(|| io::stderr().write(buf))
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.0.suspend(|| io::stderr().flush())
+    }
+}
+
+/// Shows `progress` on stderr: the files scanned so far, the TODOs found in them and the file
+/// being scanned, with the total number of files for a bar.
+fn start_progress(progress: &ProgressBar, kind: Progress) {
+    let template = match kind {
+        Progress::Spinner => "{spinner} {pos} files, {prefix} TODOs {wide_msg}",
+        Progress::Bar => "{bar:30} {pos}/{len} files, {prefix} TODOs {wide_msg}",
+    };
+    progress.set_style(ProgressStyle::with_template(template).unwrap());
+    progress.set_prefix("0");
+    progress.set_draw_target(ProgressDrawTarget::stderr());
+    progress.enable_steady_tick(Duration::from_millis(100));
+}
+
+/// Counts the entries written on the progress bar.
+struct ProgressWriter<W> {
+    inner: W,
+    progress: ProgressBar,
+    todos: usize,
+}
+
+impl<W: TodoWriter> TodoWriter for ProgressWriter<W> {
+    fn write_entry(&mut self, entry: &TodoEntry) -> io::Result<()> {
+        self.todos += 1;
+        self.progress.set_prefix(self.todos.to_string());
+        self.inner.write_entry(entry)
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        self.inner.finish()
+    }
+}
+
+/// The `--stats-json` object: the scan statistics, with the TODO budget and whether the run
+/// went over it.
+#[derive(Serialize)]
+struct StatsJson<'a> {
+    #[serde(flatten)]
+    stats: &'a ScanStats,
+    budget: Option<usize>,
+    over_budget: bool,
+}
+
+/// Prints a summary of `stats` to stderr, so it stays out of output written to stdout, with the
+/// `budget` of TODOs if there is one.
+fn print_stats(stats: &ScanStats, budget: Option<usize>) {
+    let budget = budget.map_or(String::new(), |budget| format!(" (budget {})", budget));
+    eprintln!(
+        "Scanned {} files in {:.2}s, {} with TODOs: {} TODOs{}",
+        stats.files_scanned,
+        stats.elapsed.as_secs_f64(),
+        stats.files_with_todos,
+        stats.todos(),
+        budget
+    );
+    for (language, todos) in &stats.todos_by_language {
+        eprintln!("  {}: {}", language, todos);
+    }
+}
+
+/// Parses a `YYYY-MM-DD` date, or `today` for the local date.
+fn parse_date(date: &str) -> Result<NaiveDate, String> {
+    if date == "today" {
+        return Ok(Local::now().date_naive());
+    }
+    NaiveDate::parse_from_str(date, "%Y-%m-%d").map_err(|_| "expected YYYY-MM-DD or today".into())
+}
+
+fn parse_delimiter(delimiter: &str) -> Result<u8, String> {
+    match delimiter {
+        "," => Ok(b','),
+        "\\t" | "\t" | "tab" => Ok(b'\t'),
+        ";" => Ok(b';'),
+        "|" => Ok(b'|'),
+        _ => Err("expected `,`, `;`, `|` or a tab (`\\t`)".into()),
+    }
+}
+
+/// Parses a comma-separated list of `ext=syntax` pairs, such as `proto=hash,sol=slashes`.
+fn parse_added_extensions(pairs: &str) -> Result<Vec<(String, CommentSyntax)>, String> {
+    pairs
+        .split(',')
+        .map(str::trim)
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (ext, syntax) = pair
+                .split_once('=')
+                .ok_or_else(|| format!("expected ext=syntax, got \"{}\"", pair))?;
+            let syntax = CommentSyntax::from_name(syntax).ok_or_else(|| {
+                format!(
+                    "unknown comment syntax \"{}\". Valid syntaxes are: slashes, hash, dashes, \
+                     semicolon, percent, html",
+                    syntax
+                )
+            })?;
+            Ok((ext.trim_start_matches('.').to_string(), syntax))
+        })
+        .collect()
+}
+
+/// Parses a comma-separated list of column names, failing with the valid names on a typo.
+fn parse_columns(names: &str) -> Result<Vec<Column>, String> {
+    names
+        .split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(|name| {
+            Column::from_name(name).ok_or_else(|| {
+                let valid: Vec<_> = Column::ALL
+                    .iter()
+                    .map(|column| column.name().to_lowercase())
+                    .collect();
+                format!(
+                    "unknown column \"{}\". Valid columns are: {}",
+                    name,
+                    valid.join(", ")
+                )
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,1605 @@
+//! Output formats for the entries found by a [`Scanner`](crate::utils::Scanner).
+
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use chrono::SecondsFormat;
+use serde_json::{json, Value};
+
+use crate::utils::{common_root, TodoEntry};
+
+mod header;
+
+pub use header::Header;
+
+/// Receives the entries found by a scan, in the order they were found.
+pub trait TodoWriter {
+    fn write_entry(&mut self, entry: &TodoEntry) -> io::Result<()>;
+
+    /// Writes all the entries found in `file` together, for output grouped by file. By default
+    /// they are written one by one.
+    fn write_group(&mut self, file: &str, entries: &[TodoEntry]) -> io::Result<()> {
+        let _ = file;
+        entries.iter().try_for_each(|entry| self.write_entry(entry))
+    }
+
+    /// Completes the output once the scan is done. Formats that need a closing delimiter write
+    /// it here.
+    fn finish(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl<T: TodoWriter + ?Sized> TodoWriter for Box<T> {
+    fn write_entry(&mut self, entry: &TodoEntry) -> io::Result<()> {
+        (**self).write_entry(entry)
+    }
+
+    fn write_group(&mut self, file: &str, entries: &[TodoEntry]) -> io::Result<()> {
+        (**self).write_group(file, entries)
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        (**self).finish()
+    }
+}
+
+/// Settings shared by the output formats.
+#[derive(Debug, Clone)]
+pub struct OutputOptions {
+    /// The columns of the tabular formats.
+    pub columns: Vec<Column>,
+    /// The scanned directory, shown in report headers.
+    pub root: String,
+    /// The directories scanned besides `root`. SARIF URIs are relative to the directory they
+    /// all share.
+    pub other_roots: Vec<String>,
+    /// The field delimiter of the CSV format, such as `b'\t'` for TSV.
+    pub delimiter: u8,
+    /// SARIF result levels by tag, such as `FIXME` to `warning`. Other tags are `note`.
+    pub sarif_levels: HashMap<String, String>,
+    /// The command of the GitHub Actions annotations, `warning` or `notice`.
+    pub annotation_level: AnnotationLevel,
+    /// The header row of the tabular formats.
+    pub header: Header,
+    /// Whether CSV cells that spreadsheets would run as formulas get a leading `'`.
+    pub sanitize: bool,
+    /// Whether [`Format::create`] adds to an existing CSV file instead of replacing it.
+    pub append: bool,
+    /// How the entries are grouped, when they are passed through a [`GroupingWriter`].
+    pub group_by: Option<GroupBy>,
+}
+
+impl OutputOptions {
+    /// The header row to write, or `None` for [`Header::Omit`]. Fails if a custom header doesn't
+    /// have a name per column.
+    pub fn header_names(&self) -> io::Result<Option<Vec<String>>> {
+        self.header.names(&self.columns)
+    }
+}
+
+impl Default for OutputOptions {
+    fn default() -> Self {
+        Self {
+            columns: Column::DEFAULT.to_vec(),
+            root: String::from("."),
+            other_roots: Vec::new(),
+            delimiter: b',',
+            sarif_levels: HashMap::new(),
+            annotation_level: AnnotationLevel::Warning,
+            header: Header::Columns,
+            sanitize: true,
+            append: false,
+            group_by: None,
+        }
+    }
+}
+
+/// The supported `--format` values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Format {
+    #[default]
+    Csv,
+    Json,
+    JsonLines,
+    Markdown,
+    Html,
+    Sarif,
+    GhAnnotations,
+    Junit,
+    #[cfg(feature = "yaml")]
+    Yaml,
+    Xml,
+    Grep,
+    Org,
+    #[cfg(feature = "xlsx")]
+    Xlsx,
+    #[cfg(feature = "sqlite")]
+    Sqlite,
+}
+
+impl Format {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "csv" => Some(Self::Csv),
+            "json" => Some(Self::Json),
+            "jsonl" => Some(Self::JsonLines),
+            "markdown" | "md" => Some(Self::Markdown),
+            "html" => Some(Self::Html),
+            "sarif" => Some(Self::Sarif),
+            "gh-annotations" => Some(Self::GhAnnotations),
+            "junit" => Some(Self::Junit),
+            #[cfg(feature = "yaml")]
+            "yaml" | "yml" => Some(Self::Yaml),
+            "xml" => Some(Self::Xml),
+            "grep" => Some(Self::Grep),
+            "org" => Some(Self::Org),
+            #[cfg(feature = "xlsx")]
+            "xlsx" => Some(Self::Xlsx),
+            #[cfg(feature = "sqlite")]
+            "sqlite" => Some(Self::Sqlite),
+            _ => None,
+        }
+    }
+
+    /// Creates a writer for this format that writes to `path`, or to stdout if `path` is `-`.
+    /// GitHub Actions annotations always go to stdout, next to a CSV written to `path`. With
+    /// [`OutputOptions::append`], a CSV file is added to, and other formats are refused.
+    pub fn create(self, path: &Path, options: &OutputOptions) -> io::Result<Box<dyn TodoWriter>> {
+        if path == Path::new("-") {
+            return self.writer(BufWriter::new(io::stdout().lock()), options);
+        }
+        match self {
+            Self::Csv if options.append => {
+                // The header is only written to a new file, once it's known to match.
+                let header =
+                    if options
+                        .header
+                        .needed_in(path, &options.columns, options.delimiter)?
+                    {
+                        options.header.clone()
+                    } else {
+                        Header::Omit
+                    };
+                let file = OpenOptions::new().append(true).create(true).open(path)?;
+                let options = OutputOptions {
+                    header,
+                    ..options.clone()
+                };
+                self.writer(BufWriter::new(file), &options)
+            }
+            _ if options.append && self != Self::GhAnnotations => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "only csv output can be appended to",
+            )),
+            Self::GhAnnotations => Ok(Box::new(TeeWriter(vec![
+                Self::Csv.create(path, options)?,
+                Self::GhAnnotations.writer(io::stdout().lock(), options)?,
+            ]))),
+            #[cfg(feature = "xlsx")]
+            Self::Xlsx => Ok(Box::new(XlsxWriter::new(
+                path,
+                options.columns.clone(),
+                options.header_names()?,
+            ))),
+            #[cfg(feature = "sqlite")]
+            Self::Sqlite => Ok(Box::new(SqliteWriter::open(path, &options.root)?)),
+            _ => self.writer(BufWriter::new(File::create(path)?), options),
+        }
+    }
+
+    /// Creates a writer for this format. The CSV header is written straight away.
+    pub fn writer<W: Write + 'static>(
+        self,
+        inner: W,
+        options: &OutputOptions,
+    ) -> io::Result<Box<dyn TodoWriter>> {
+        Ok(match self {
+            Self::Csv => {
+                let mut writer =
+                    CsvWriter::with_delimiter(inner, options.columns.clone(), options.delimiter)
+                        .sanitize(options.sanitize)
+                        .subtotals(options.group_by.is_some());
+                if let Some(names) = options.header_names()? {
+                    writer.write_header_names(names)?;
+                }
+                Box::new(writer)
+            }
+            Self::Json if options.group_by.is_some() => Box::new(JsonWriter::grouped(inner)),
+            Self::Html => Box::new(HtmlWriter::new(inner, &options.root)),
+            _ if options.group_by.is_some() => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "only csv, json and html output can be grouped by file",
+                ))
+            }
+            Self::Json => Box::new(JsonWriter::new(inner)),
+            Self::JsonLines => Box::new(JsonLinesWriter::new(inner)),
+            Self::Markdown => Box::new(MarkdownWriter::new(inner)),
+            Self::Sarif => Box::new(
+                SarifWriter::new(inner, &options.root, options.sarif_levels.clone())
+                    .other_roots(options.other_roots.clone()),
+            ),
+            Self::GhAnnotations => {
+                Box::new(GhAnnotationWriter::new(inner, options.annotation_level))
+            }
+            Self::Junit => Box::new(
+                JunitWriter::new(inner, &options.root).other_roots(options.other_roots.clone()),
+            ),
+            #[cfg(feature = "yaml")]
+            Self::Yaml => Box::new(YamlWriter::new(inner)),
+            Self::Xml => Box::new(XmlWriter::new(inner)),
+            Self::Grep => Box::new(GrepWriter::new(inner)),
+            Self::Org => Box::new(OrgWriter::new(inner, &options.root)),
+            #[cfg(feature = "xlsx")]
+            Self::Xlsx => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "xlsx output needs a file path",
+                ))
+            }
+            #[cfg(feature = "sqlite")]
+            Self::Sqlite => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "sqlite output needs a database path",
+                ))
+            }
+        })
+    }
+}
+
+/// The workflow command of a GitHub Actions annotation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnnotationLevel {
+    Warning,
+    Notice,
+}
+
+impl AnnotationLevel {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "warning" => Some(Self::Warning),
+            "notice" => Some(Self::Notice),
+            _ => None,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Warning => "warning",
+            Self::Notice => "notice",
+        }
+    }
+}
+
+/// Passes every entry to each of the writers.
+pub struct TeeWriter(pub Vec<Box<dyn TodoWriter>>);
+
+impl TodoWriter for TeeWriter {
+    fn write_entry(&mut self, entry: &TodoEntry) -> io::Result<()> {
+        self.0
+            .iter_mut()
+            .try_for_each(|writer| writer.write_entry(entry))
+    }
+
+    fn write_group(&mut self, file: &str, entries: &[TodoEntry]) -> io::Result<()> {
+        self.0
+            .iter_mut()
+            .try_for_each(|writer| writer.write_group(file, entries))
+    }
+
+    /// Finishes every writer, returning the first error.
+    fn finish(&mut self) -> io::Result<()> {
+        self.0
+            .iter_mut()
+            .map(|writer| writer.finish())
+            .fold(Ok(()), Result::and)
+    }
+}
+
+/// The supported `--sort` orders of the entries.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Sort {
+    /// In the order they were found, written as they are found.
+    None,
+    /// By path, compared byte by byte, then by line.
+    #[default]
+    Path,
+    /// By line, then by path.
+    Line,
+    /// By tag, then by path and line.
+    Tag,
+    /// By comment, then by path and line.
+    Comment,
+}
+
+impl Sort {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "none" => Some(Self::None),
+            "path" => Some(Self::Path),
+            "line" => Some(Self::Line),
+            "tag" => Some(Self::Tag),
+            "comment" => Some(Self::Comment),
+            _ => None,
+        }
+    }
+
+    /// Sorts `entries` stably in this order, so entries that compare equal keep the order they
+    /// were found in.
+    pub fn sort(self, entries: &mut [TodoEntry]) {
+        let position = |entry: &TodoEntry| (entry.cell, entry.line, entry.todo.column);
+        match self {
+            Self::None => {}
+            Self::Path => {
+                entries.sort_by(|a, b| (&a.file, position(a)).cmp(&(&b.file, position(b))))
+            }
+            Self::Line => {
+                entries.sort_by(|a, b| (position(a), &a.file).cmp(&(position(b), &b.file)))
+            }
+            Self::Tag => entries.sort_by(|a, b| {
+                (&a.todo.tag, &a.file, position(a)).cmp(&(&b.todo.tag, &b.file, position(b)))
+            }),
+            Self::Comment => entries.sort_by(|a, b| {
+                (&a.todo.comment, &a.file, position(a)).cmp(&(
+                    &b.todo.comment,
+                    &b.file,
+                    position(b),
+                ))
+            }),
+        }
+    }
+}
+
+/// Holds back the entries until the scan is done, then passes them to `inner` sorted.
+pub struct SortingWriter<W: TodoWriter> {
+    inner: W,
+    sort: Sort,
+    entries: Vec<TodoEntry>,
+}
+
+impl<W: TodoWriter> SortingWriter<W> {
+    pub fn new(inner: W, sort: Sort) -> Self {
+        Self {
+            inner,
+            sort,
+            entries: Vec::new(),
+        }
+    }
+
+    /// Returns the writer that the entries were passed to, once finished.
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+impl<W: TodoWriter> TodoWriter for SortingWriter<W> {
+    fn write_entry(&mut self, entry: &TodoEntry) -> io::Result<()> {
+        self.entries.push(entry.clone());
+        Ok(())
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        let mut entries = std::mem::take(&mut self.entries);
+        self.sort.sort(&mut entries);
+        entries
+            .iter()
+            .try_for_each(|entry| self.inner.write_entry(entry))?;
+        self.inner.finish()
+    }
+}
+
+/// The supported `--group-by` values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GroupBy {
+    File,
+}
+
+impl GroupBy {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "file" => Some(Self::File),
+            _ => None,
+        }
+    }
+}
+
+/// Holds back the entries until the scan is done, then passes them to `inner` a file at a time,
+/// with the files in path order and each file's entries in `sort` order.
+pub struct GroupingWriter<W: TodoWriter> {
+    inner: SortingWriter<W>,
+}
+
+impl<W: TodoWriter> GroupingWriter<W> {
+    pub fn new(inner: W, sort: Sort) -> Self {
+        Self {
+            inner: SortingWriter::new(inner, sort),
+        }
+    }
+
+    /// Returns the writer that the groups were passed to, once finished.
+    pub fn into_inner(self) -> W {
+        self.inner.into_inner()
+    }
+}
+
+impl<W: TodoWriter> TodoWriter for GroupingWriter<W> {
+    fn write_entry(&mut self, entry: &TodoEntry) -> io::Result<()> {
+        self.inner.write_entry(entry)
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        let SortingWriter {
+            inner,
+            sort,
+            entries,
+        } = &mut self.inner;
+        sort.sort(entries);
+        entries.sort_by(|a, b| a.file.cmp(&b.file));
+        for group in entries.chunk_by(|a, b| a.file == b.file) {
+            inner.write_group(&group[0].file, group)?;
+        }
+        entries.clear();
+        inner.finish()
+    }
+}
+
+/// Writes how many entries were found instead of the entries, once the scan is done: the total
+/// on a line of its own, or with `by` a `count<TAB>path` line per file, the most first.
+pub struct CountWriter<W: Write> {
+    inner: W,
+    by: Option<GroupBy>,
+    counts: HashMap<String, usize>,
+    total: usize,
+}
+
+impl<W: Write> CountWriter<W> {
+    pub fn new(inner: W, by: Option<GroupBy>) -> Self {
+        Self {
+            inner,
+            by,
+            counts: HashMap::new(),
+            total: 0,
+        }
+    }
+}
+
+impl<W: Write> TodoWriter for CountWriter<W> {
+    fn write_entry(&mut self, entry: &TodoEntry) -> io::Result<()> {
+        self.total += 1;
+        if self.by == Some(GroupBy::File) {
+            *self.counts.entry(entry.file.clone()).or_default() += 1;
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        match self.by {
+            None => writeln!(self.inner, "{}", self.total)?,
+            Some(GroupBy::File) => {
+                let mut counts: Vec<_> = self.counts.drain().collect();
+                counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+                for (file, count) in counts {
+                    writeln!(self.inner, "{}\t{}", count, file)?;
+                }
+            }
+        }
+        self.inner.flush()
+    }
+}
+
+/// A CSV column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Column {
+    File,
+    Language,
+    Line,
+    Column,
+    Offset,
+    Tag,
+    Author,
+    Issue,
+    Priority,
+    Due,
+    Overdue,
+    BlameAuthor,
+    AuthorDate,
+    Commit,
+    AgeDays,
+    Modified,
+    Doc,
+    Comment,
+    Link,
+    RawLine,
+    Context,
+}
+
+impl Column {
+    /// The columns written unless the scanner adds optional ones.
+    pub const DEFAULT: [Self; 8] = [
+        Self::File,
+        Self::Line,
+        Self::Tag,
+        Self::Author,
+        Self::Issue,
+        Self::Priority,
+        Self::Due,
+        Self::Comment,
+    ];
+
+    /// Every column, in their default order.
+    pub const ALL: [Self; 21] = [
+        Self::File,
+        Self::Language,
+        Self::Line,
+        Self::Column,
+        Self::Offset,
+        Self::Tag,
+        Self::Author,
+        Self::Issue,
+        Self::Priority,
+        Self::Due,
+        Self::Overdue,
+        Self::BlameAuthor,
+        Self::AuthorDate,
+        Self::Commit,
+        Self::AgeDays,
+        Self::Modified,
+        Self::Doc,
+        Self::Comment,
+        Self::Link,
+        Self::RawLine,
+        Self::Context,
+    ];
+
+    /// Looks up a column by its header, ignoring case.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|column| column.name().eq_ignore_ascii_case(name))
+    }
+
+    /// The header of the column.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::File => "File",
+            Self::Language => "Language",
+            Self::Line => "Line",
+            Self::Column => "Column",
+            Self::Offset => "Offset",
+            Self::Tag => "Tag",
+            Self::Author => "Author",
+            Self::Issue => "Issue",
+            Self::Priority => "Priority",
+            Self::Due => "Due",
+            Self::Overdue => "Overdue",
+            Self::BlameAuthor => "BlameAuthor",
+            Self::AuthorDate => "AuthorDate",
+            Self::Commit => "Commit",
+            Self::AgeDays => "AgeDays",
+            Self::Modified => "Modified",
+            Self::Doc => "Doc",
+            Self::Comment => "Comment",
+            Self::RawLine => "RawLine",
+            Self::Context => "Context",
+            Self::Link => "Link",
+        }
+    }
+
+    fn value(self, entry: &TodoEntry) -> String {
+        let todo = &entry.todo;
+        match self {
+            Self::File => entry.file.clone(),
+            Self::Language => entry.language.unwrap_or_default().to_string(),
+            Self::Line => entry.location(),
+            Self::Column => todo.column.to_string(),
+            Self::Offset => entry.offset.map(|o| o.to_string()).unwrap_or_default(),
+            Self::Tag => todo.tag.clone(),
+            Self::Author => todo.author.clone().unwrap_or_default(),
+            Self::Issue => todo.issue.clone().unwrap_or_default(),
+            Self::Priority => todo.priority.clone().unwrap_or_default(),
+            Self::Due => todo.due.map(|due| due.to_string()).unwrap_or_default(),
+            Self::Overdue => todo.overdue.map(|o| o.to_string()).unwrap_or_default(),
+            Self::BlameAuthor => entry.blame_author.clone().unwrap_or_default(),
+            Self::AuthorDate => entry.author_date.map(|d| d.to_string()).unwrap_or_default(),
+            Self::Commit => entry.commit.clone().unwrap_or_default(),
+            Self::AgeDays => entry.age_days.map(|d| d.to_string()).unwrap_or_default(),
+            Self::Modified => entry
+                .modified
+                .map(|modified| modified.to_rfc3339_opts(SecondsFormat::Secs, true))
+                .unwrap_or_default(),
+            Self::Doc => todo.doc.to_string(),
+            Self::Comment => todo.comment.clone(),
+            Self::RawLine => entry.raw_line.clone().unwrap_or_default(),
+            Self::Link => entry.link.clone().unwrap_or_default(),
+            Self::Context => entry.context.as_deref().unwrap_or_default().join("\n"),
+        }
+    }
+}
+
+/// The I/O error behind `err`, if it is one, so that callers can still tell a closed pipe from
+/// other failures.
+fn io_error(err: csv::Error) -> io::Error {
+    if !err.is_io_error() {
+        return io::Error::other(err);
+    }
+    match err.into_kind() {
+        csv::ErrorKind::Io(err) => err,
+        _ => unreachable!("is_io_error checked the kind"),
+    }
+}
+
+/// Writes the [`Column::DEFAULT`] columns, without a header.
+impl<W: Write> TodoWriter for csv::Writer<W> {
+    fn write_entry(&mut self, entry: &TodoEntry) -> io::Result<()> {
+        self.write_record(Column::DEFAULT.iter().map(|column| column.value(entry)))
+            .map_err(io_error)?;
+        Ok(())
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        self.flush()
+    }
+}
+
+/// Writes one CSV record per entry with the given columns.
+pub struct CsvWriter<W: Write> {
+    writer: csv::Writer<W>,
+    columns: Vec<Column>,
+    sanitize: bool,
+    /// The entries written so far, when each group is followed by a subtotal row.
+    total: Option<usize>,
+}
+
+impl<W: Write> CsvWriter<W> {
+    pub fn new(inner: W, columns: Vec<Column>) -> Self {
+        Self::with_delimiter(inner, columns, b',')
+    }
+
+    pub fn with_delimiter(inner: W, columns: Vec<Column>, delimiter: u8) -> Self {
+        Self {
+            writer: csv::WriterBuilder::new()
+                .delimiter(delimiter)
+                .from_writer(inner),
+            columns,
+            sanitize: false,
+            total: None,
+        }
+    }
+
+    /// Prefixes cells starting with `=`, `+`, `-`, `@`, a tab or a carriage return with `'`, so
+    /// spreadsheets show them as text instead of running them as formulas.
+    pub fn sanitize(mut self, yes: bool) -> Self {
+        self.sanitize = yes;
+        self
+    }
+
+    /// Follows each group of entries from [`TodoWriter::write_group`] with a subtotal row, and
+    /// ends the output with a grand total row. These rows have the file's path, or nothing for
+    /// the grand total, in the File column, `TOTAL: ` and the count in the Comment column, or
+    /// in the last other one if there is no Comment column, and every other cell empty.
+    pub fn subtotals(mut self, yes: bool) -> Self {
+        self.total = yes.then_some(0);
+        self
+    }
+
+    fn write_total(&mut self, file: &str, count: usize) -> io::Result<()> {
+        let total = self
+            .columns
+            .iter()
+            .position(|&c| c == Column::Comment)
+            .or_else(|| self.columns.iter().rposition(|&c| c != Column::File))
+            .unwrap_or_default();
+        let row = self
+            .columns
+            .iter()
+            .enumerate()
+            .map(|(i, &column)| match column {
+                _ if i == total => format!("TOTAL: {}", count),
+                Column::File if self.sanitize => sanitize_formula(file.to_string()),
+                Column::File => file.to_string(),
+                _ => String::new(),
+            });
+        self.writer
+            .write_record(row.collect::<Vec<_>>())
+            .map_err(io_error)?;
+        Ok(())
+    }
+
+    /// Flushes and returns the underlying writer.
+    pub fn into_inner(self) -> io::Result<W> {
+        self.writer
+            .into_inner()
+            .map_err(|err| io::Error::other(err.to_string()))
+    }
+
+    /// Writes the column names as the header row.
+    pub fn write_header(&mut self) -> io::Result<()> {
+        let names: Vec<_> = self.columns.iter().map(|column| column.name()).collect();
+        self.write_header_names(names)
+    }
+
+    pub fn write_header_names<I, S>(&mut self, names: I) -> io::Result<()>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<[u8]>,
+    {
+        self.writer.write_record(names).map_err(io_error)?;
+        Ok(())
+    }
+}
+
+impl<W: Write> TodoWriter for CsvWriter<W> {
+    fn write_entry(&mut self, entry: &TodoEntry) -> io::Result<()> {
+        self.writer
+            .write_record(self.columns.iter().map(|column| {
+                let value = column.value(entry);
+                if self.sanitize {
+                    sanitize_formula(value)
+                } else {
+                    value
+                }
+            }))
+            .map_err(io_error)?;
+        Ok(())
+    }
+
+    fn write_group(&mut self, file: &str, entries: &[TodoEntry]) -> io::Result<()> {
+        entries
+            .iter()
+            .try_for_each(|entry| self.write_entry(entry))?;
+        if let Some(total) = &mut self.total {
+            *total += entries.len();
+            self.write_total(file, entries.len())?;
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        if let Some(total) = self.total {
+            self.write_total("", total)?;
+        }
+        self.writer.flush()
+    }
+}
+
+/// Neutralizes CSV injection, per the OWASP guidance.
+fn sanitize_formula(value: String) -> String {
+    if value.starts_with(['=', '+', '-', '@', '\t', '\r']) {
+        format!("'{}", value)
+    } else {
+        value
+    }
+}
+
+/// Writes a JSON array with one object per entry, or `[]` when nothing was found.
+pub struct JsonWriter<W: Write> {
+    inner: W,
+    entries: usize,
+    grouped: bool,
+}
+
+impl<W: Write> JsonWriter<W> {
+    pub fn new(inner: W) -> Self {
+        Self {
+            inner,
+            entries: 0,
+            grouped: false,
+        }
+    }
+
+    /// Writes an object instead, with each file's path as a key for the array of its entries,
+    /// from [`TodoWriter::write_group`].
+    pub fn grouped(inner: W) -> Self {
+        Self {
+            grouped: true,
+            ..Self::new(inner)
+        }
+    }
+}
+
+impl<W: Write> TodoWriter for JsonWriter<W> {
+    fn write_entry(&mut self, entry: &TodoEntry) -> io::Result<()> {
+        let separator = if self.entries == 0 { "[\n  " } else { ",\n  " };
+        self.inner.write_all(separator.as_bytes())?;
+        serde_json::to_writer(&mut self.inner, entry)?;
+        self.entries += 1;
+        Ok(())
+    }
+
+    fn write_group(&mut self, file: &str, entries: &[TodoEntry]) -> io::Result<()> {
+        if !self.grouped {
+            return entries.iter().try_for_each(|entry| self.write_entry(entry));
+        }
+        let separator = if self.entries == 0 { "{\n  " } else { ",\n  " };
+        self.inner.write_all(separator.as_bytes())?;
+        serde_json::to_writer(&mut self.inner, file)?;
+        self.inner.write_all(b": [")?;
+        for (i, entry) in entries.iter().enumerate() {
+            let separator = if i == 0 { "\n    " } else { ",\n    " };
+            self.inner.write_all(separator.as_bytes())?;
+            serde_json::to_writer(&mut self.inner, entry)?;
+        }
+        self.inner.write_all(b"\n  ]")?;
+        self.entries += 1;
+        Ok(())
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        let closing = match (self.grouped, self.entries) {
+            (false, 0) => "[]\n",
+            (false, _) => "\n]\n",
+            (true, 0) => "{}\n",
+            (true, _) => "\n}\n",
+        };
+        self.inner.write_all(closing.as_bytes())?;
+        self.inner.flush()
+    }
+}
+
+/// Writes one JSON object per line as soon as each entry is found. Every line is serialized
+/// before it is written, so output cut short by an error ends with a complete line.
+pub struct JsonLinesWriter<W: Write> {
+    inner: W,
+}
+
+impl<W: Write> JsonLinesWriter<W> {
+    pub fn new(inner: W) -> Self {
+        Self { inner }
+    }
+}
+
+impl<W: Write> TodoWriter for JsonLinesWriter<W> {
+    fn write_entry(&mut self, entry: &TodoEntry) -> io::Result<()> {
+        let mut line = serde_json::to_vec(entry)?;
+        line.push(b'\n');
+        self.inner.write_all(&line)
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Writes a YAML sequence with a mapping per entry, or `[]` when nothing was found. Entries are
+/// written as they are found.
+#[cfg(feature = "yaml")]
+pub struct YamlWriter<W: Write> {
+    inner: W,
+    entries: usize,
+}
+
+#[cfg(feature = "yaml")]
+impl<W: Write> YamlWriter<W> {
+    pub fn new(inner: W) -> Self {
+        Self { inner, entries: 0 }
+    }
+}
+
+#[cfg(feature = "yaml")]
+impl<W: Write> TodoWriter for YamlWriter<W> {
+    fn write_entry(&mut self, entry: &TodoEntry) -> io::Result<()> {
+        let item = serde_yaml::to_string(std::slice::from_ref(entry)).map_err(io::Error::other)?;
+        self.inner.write_all(item.as_bytes())?;
+        self.entries += 1;
+        Ok(())
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        if self.entries == 0 {
+            writeln!(self.inner, "[]")?;
+        }
+        self.inner.flush()
+    }
+}
+
+/// Writes a `<todos>` document with a `<todo>` element per entry, as the entries are found.
+pub struct XmlWriter<W: Write> {
+    inner: W,
+    started: bool,
+}
+
+impl<W: Write> XmlWriter<W> {
+    pub fn new(inner: W) -> Self {
+        Self {
+            inner,
+            started: false,
+        }
+    }
+
+    fn start(&mut self) -> io::Result<()> {
+        if !self.started {
+            writeln!(self.inner, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+            writeln!(self.inner, "<todos>")?;
+            self.started = true;
+        }
+        Ok(())
+    }
+}
+
+impl<W: Write> TodoWriter for XmlWriter<W> {
+    fn write_entry(&mut self, entry: &TodoEntry) -> io::Result<()> {
+        self.start()?;
+        let cell = entry
+            .cell
+            .map(|cell| format!(r#" cell="{}""#, cell))
+            .unwrap_or_default();
+        writeln!(
+            self.inner,
+            r#"  <todo file="{}"{} line="{}" tag="{}"><comment>{}</comment></todo>"#,
+            escape_xml(&entry.file),
+            cell,
+            entry.line,
+            escape_xml(&entry.todo.tag),
+            escape_xml(&entry.todo.comment)
+        )
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        self.start()?;
+        writeln!(self.inner, "</todos>")?;
+        self.inner.flush()
+    }
+}
+
+/// Writes `path:line:column: TAG: comment` lines, as understood by vim's `:cfile`, Emacs
+/// compilation-mode and grep-consuming scripts. Paths under the current directory are made
+/// relative to it.
+pub struct GrepWriter<W: Write> {
+    inner: W,
+    current_dir: PathBuf,
+}
+
+impl<W: Write> GrepWriter<W> {
+    pub fn new(inner: W) -> Self {
+        Self {
+            inner,
+            current_dir: std::env::current_dir().unwrap_or_default(),
+        }
+    }
+}
+
+impl<W: Write> TodoWriter for GrepWriter<W> {
+    fn write_entry(&mut self, entry: &TodoEntry) -> io::Result<()> {
+        let file = if Path::new(&entry.file).is_relative() {
+            relative_uri(&entry.file, "")
+        } else {
+            relative_uri(&entry.file, &self.current_dir)
+        };
+        let comment = entry
+            .todo
+            .comment
+            .replace("\r\n", " ")
+            .replace(['\r', '\n'], " ");
+        writeln!(
+            self.inner,
+            "{}:{}:{}: {}: {}",
+            file, entry.line, entry.todo.column, entry.todo.tag, comment
+        )
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Writes an Org-mode outline: a headline for the scanned directory, a second-level headline per
+/// file, and a `TODO` headline per entry with a `:PROPERTIES:` drawer.
+pub struct OrgWriter<W: Write> {
+    inner: W,
+    root: String,
+    started: bool,
+    file: Option<String>,
+}
+
+impl<W: Write> OrgWriter<W> {
+    pub fn new(inner: W, root: &str) -> Self {
+        Self {
+            inner,
+            root: root.to_string(),
+            started: false,
+            file: None,
+        }
+    }
+
+    fn start(&mut self) -> io::Result<()> {
+        if !self.started {
+            writeln!(self.inner, "* TODOs in {}", escape_org(&self.root))?;
+            self.started = true;
+        }
+        Ok(())
+    }
+}
+
+impl<W: Write> TodoWriter for OrgWriter<W> {
+    fn write_entry(&mut self, entry: &TodoEntry) -> io::Result<()> {
+        self.start()?;
+        if self.file.as_deref() != Some(entry.file.as_str()) {
+            writeln!(self.inner, "** {}", escape_org(&entry.file))?;
+            self.file = Some(entry.file.clone());
+        }
+        writeln!(self.inner, "*** TODO {}", escape_org(&entry.todo.comment))?;
+        writeln!(self.inner, ":PROPERTIES:")?;
+        writeln!(self.inner, ":FILE:     {}", entry.file)?;
+        writeln!(self.inner, ":LINE:     {}", entry.location())?;
+        writeln!(self.inner, ":TAG:      {}", entry.todo.tag)?;
+        writeln!(self.inner, ":END:")
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        self.start()?;
+        self.inner.flush()
+    }
+}
+
+/// Flattens `text` onto one line and puts a zero-width space before a leading `*`, the escape
+/// the Org manual suggests, so it can't start a headline.
+fn escape_org(text: &str) -> String {
+    let text = text.replace("\r\n", " ").replace(['\r', '\n'], " ");
+    if text.starts_with('*') {
+        format!("\u{200B}{}", text)
+    } else {
+        text
+    }
+}
+
+/// Writes a GitHub-flavoured checklist with a `###` heading per file.
+pub struct MarkdownWriter<W: Write> {
+    inner: W,
+    file: Option<String>,
+}
+
+impl<W: Write> MarkdownWriter<W> {
+    pub fn new(inner: W) -> Self {
+        Self { inner, file: None }
+    }
+}
+
+impl<W: Write> TodoWriter for MarkdownWriter<W> {
+    fn write_entry(&mut self, entry: &TodoEntry) -> io::Result<()> {
+        if self.file.as_deref() != Some(entry.file.as_str()) {
+            if self.file.is_some() {
+                writeln!(self.inner)?;
+            }
+            writeln!(self.inner, "### {}\n", escape_markdown(&entry.file))?;
+            self.file = Some(entry.file.clone());
+        }
+        let location = match entry.cell {
+            Some(cell) => format!("cell {}, L{}", cell, entry.line),
+            None => format!("L{}", entry.line),
+        };
+        writeln!(
+            self.inner,
+            "- [ ] **{}**: {}",
+            location,
+            escape_markdown(&entry.todo.comment)
+        )
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        if self.file.is_none() {
+            writeln!(self.inner, "No TODOs found.")?;
+        }
+        self.inner.flush()
+    }
+}
+
+/// Writes a self-contained HTML report with a sortable, filterable table. The rows are kept until
+/// [`TodoWriter::finish`] so the header can show the total.
+pub struct HtmlWriter<W: Write> {
+    inner: W,
+    root: String,
+    rows: Vec<String>,
+    /// The `<tbody>` of each file, headed by its path, for output grouped by file.
+    sections: Vec<String>,
+    count: usize,
+}
+
+impl<W: Write> HtmlWriter<W> {
+    pub fn new(inner: W, root: &str) -> Self {
+        Self {
+            inner,
+            root: root.to_string(),
+            rows: Vec::new(),
+            sections: Vec::new(),
+            count: 0,
+        }
+    }
+
+    fn row(entry: &TodoEntry) -> String {
+        format!(
+            "<tr><td><code>{}</code></td><td data-sort=\"{}\">{}</td><td>{}</td><td>{}</td></tr>",
+            escape_xml(&entry.file),
+            entry.line,
+            escape_xml(&entry.location()),
+            escape_xml(&entry.todo.tag),
+            escape_xml(&entry.todo.comment),
+        )
+    }
+}
+
+impl<W: Write> TodoWriter for HtmlWriter<W> {
+    fn write_entry(&mut self, entry: &TodoEntry) -> io::Result<()> {
+        self.rows.push(Self::row(entry));
+        self.count += 1;
+        Ok(())
+    }
+
+    fn write_group(&mut self, file: &str, entries: &[TodoEntry]) -> io::Result<()> {
+        let heading = format!(
+            "<tr class=\"file\"><th colspan=\"4\"><code>{}</code>: {} found</th></tr>",
+            escape_xml(file),
+            entries.len()
+        );
+        let rows: Vec<_> = std::iter::once(heading)
+            .chain(entries.iter().map(Self::row))
+            .collect();
+        self.sections
+            .push(format!("<tbody>\n{}\n</tbody>", rows.join("\n")));
+        self.count += entries.len();
+        Ok(())
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        if self.sections.is_empty() || !self.rows.is_empty() {
+            self.sections
+                .push(format!("<tbody>\n{}\n</tbody>", self.rows.join("\n")));
+        }
+        let report = HTML_TEMPLATE
+            .replace("{root}", &escape_xml(&self.root))
+            .replace("{count}", &self.count.to_string())
+            .replace("{sections}", &self.sections.join("\n"));
+        self.inner.write_all(report.as_bytes())?;
+        self.inner.flush()
+    }
+}
+
+/// Writes a JUnit XML report with a failing `<testcase>` per entry in a `<testsuite>` for each
+/// scanned directory, once the scan is done.
+pub struct JunitWriter<W: Write> {
+    inner: W,
+    /// Each scanned directory with the cases of the entries found under it.
+    suites: Vec<(String, Vec<String>)>,
+}
+
+impl<W: Write> JunitWriter<W> {
+    pub fn new(inner: W, root: &str) -> Self {
+        Self {
+            inner,
+            suites: vec![(root.to_string(), Vec::new())],
+        }
+    }
+
+    /// Adds a suite for each of the directories scanned besides `root`.
+    pub fn other_roots(mut self, roots: Vec<String>) -> Self {
+        self.suites
+            .extend(roots.into_iter().map(|root| (root, Vec::new())));
+        self
+    }
+
+    /// The suite of the first root that `file` is under, however the paths are spelled, or
+    /// the first suite.
+    fn suite(&mut self, file: &str) -> &mut Vec<String> {
+        let file = Path::new(file);
+        let roots: Vec<_> = self
+            .suites
+            .iter()
+            .map(|(root, _)| Path::new(root))
+            .collect();
+        let base = common_root(roots.iter().copied());
+        let index = roots.iter().position(|root| {
+            let relative = root.strip_prefix(&base).ok();
+            file.starts_with(root)
+                || relative.is_some_and(|relative| file.starts_with(relative))
+                || root.canonicalize().is_ok_and(|root| file.starts_with(root))
+        });
+        &mut self.suites[index.unwrap_or(0)].1
+    }
+}
+
+impl<W: Write> TodoWriter for JunitWriter<W> {
+    fn write_entry(&mut self, entry: &TodoEntry) -> io::Result<()> {
+        let comment = escape_xml(&entry.todo.comment);
+        let case = format!(
+            "    <testcase classname=\"{}\" name=\"line {}\">\n      \
+             <failure message=\"{}\" type=\"{}\">{}</failure>\n    </testcase>\n",
+            escape_xml(&entry.file),
+            escape_xml(&entry.location()),
+            comment,
+            escape_xml(&entry.todo.tag),
+            comment,
+        );
+        self.suite(&entry.file).push(case);
+        Ok(())
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        let count: usize = self.suites.iter().map(|(_, cases)| cases.len()).sum();
+        writeln!(self.inner, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+        writeln!(
+            self.inner,
+            r#"<testsuites tests="{0}" failures="{0}" errors="0">"#,
+            count
+        )?;
+        for (root, cases) in &self.suites {
+            writeln!(
+                self.inner,
+                r#"  <testsuite name="{}" tests="{1}" failures="{1}" errors="0" skipped="0">"#,
+                escape_xml(root),
+                cases.len()
+            )?;
+            for case in cases {
+                self.inner.write_all(case.as_bytes())?;
+            }
+            writeln!(self.inner, "  </testsuite>")?;
+        }
+        writeln!(self.inner, "</testsuites>")?;
+        self.inner.flush()
+    }
+}
+
+/// Writes a SARIF 2.1.0 log with a rule per tag and a result per entry, once the scan is done.
+pub struct SarifWriter<W: Write> {
+    inner: W,
+    root: String,
+    other_roots: Vec<String>,
+    levels: HashMap<String, String>,
+    rules: Vec<String>,
+    results: Vec<Value>,
+}
+
+impl<W: Write> SarifWriter<W> {
+    pub fn new(inner: W, root: &str, levels: HashMap<String, String>) -> Self {
+        Self {
+            inner,
+            root: root.to_string(),
+            other_roots: Vec::new(),
+            levels,
+            rules: Vec::new(),
+            results: Vec::new(),
+        }
+    }
+
+    /// The directories scanned besides `root`. The URIs are then relative to the directory they
+    /// all share, the one `%SRCROOT%` stands for.
+    pub fn other_roots(mut self, roots: Vec<String>) -> Self {
+        self.other_roots = roots;
+        self
+    }
+
+    /// The URI of `file`, relative to the directory shared by the roots.
+    fn uri(&self, file: &str) -> String {
+        let roots = std::iter::once(&self.root).chain(&self.other_roots);
+        relative_uri(file, common_root(roots.map(Path::new)))
+    }
+}
+
+impl<W: Write> TodoWriter for SarifWriter<W> {
+    fn write_entry(&mut self, entry: &TodoEntry) -> io::Result<()> {
+        let tag = &entry.todo.tag;
+        let rule_index = match self.rules.iter().position(|rule| rule == tag) {
+            Some(index) => index,
+            None => {
+                self.rules.push(tag.clone());
+                self.rules.len() - 1
+            }
+        };
+        self.results.push(json!({
+            "ruleId": tag,
+            "ruleIndex": rule_index,
+            "level": self.levels.get(tag).map_or("note", String::as_str),
+            "message": { "text": entry.todo.comment },
+            "locations": [{
+                "physicalLocation": {
+                    "artifactLocation": {
+                        "uri": self.uri(&entry.file),
+                        "uriBaseId": "%SRCROOT%",
+                    },
+                    "region": { "startLine": entry.line, "startColumn": entry.todo.column },
+                },
+            }],
+        }));
+        Ok(())
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        let rules: Vec<_> = self
+            .rules
+            .iter()
+            .map(|tag| {
+                json!({
+                    "id": tag,
+                    "name": tag,
+                    "shortDescription": { "text": format!("{} comment", tag) },
+                })
+            })
+            .collect();
+        let log = json!({
+            "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+            "version": "2.1.0",
+            "runs": [{
+                "tool": {
+                    "driver": {
+                        "name": env!("CARGO_PKG_NAME"),
+                        "version": env!("CARGO_PKG_VERSION"),
+                        "rules": rules,
+                    },
+                },
+                "results": self.results,
+            }],
+        });
+        serde_json::to_writer_pretty(&mut self.inner, &log)?;
+        writeln!(self.inner)?;
+        self.inner.flush()
+    }
+}
+
+/// Writes a GitHub Actions workflow command per entry, such as
+/// `::warning file=src/lib.rs,line=42::TODO: handle retries`. Files are made relative to
+/// `GITHUB_WORKSPACE`, or to the current directory outside of Actions.
+pub struct GhAnnotationWriter<W: Write> {
+    inner: W,
+    level: AnnotationLevel,
+    workspace: PathBuf,
+}
+
+impl<W: Write> GhAnnotationWriter<W> {
+    pub fn new(inner: W, level: AnnotationLevel) -> Self {
+        let workspace = std::env::var_os("GITHUB_WORKSPACE")
+            .map(PathBuf::from)
+            .or_else(|| std::env::current_dir().ok())
+            .unwrap_or_default();
+        Self::with_workspace(inner, level, workspace)
+    }
+
+    pub fn with_workspace(inner: W, level: AnnotationLevel, workspace: PathBuf) -> Self {
+        Self {
+            inner,
+            level,
+            workspace,
+        }
+    }
+}
+
+impl<W: Write> TodoWriter for GhAnnotationWriter<W> {
+    fn write_entry(&mut self, entry: &TodoEntry) -> io::Result<()> {
+        let mut file = PathBuf::from(&entry.file);
+        if file.is_relative() {
+            if let Ok(current_dir) = std::env::current_dir() {
+                file = current_dir.join(file);
+            }
+        }
+        let file = relative_uri(file.to_str().unwrap_or_default(), &self.workspace);
+        let message = format!("{}: {}", entry.todo.tag, entry.todo.comment);
+        writeln!(
+            self.inner,
+            "::{} file={},line={}::{}",
+            self.level.name(),
+            escape_annotation_property(&file),
+            entry.line,
+            escape_annotation_data(&message)
+        )
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Escapes a workflow command message. A `::` in the message is kept, since only the first one
+/// after the properties ends the command.
+fn escape_annotation_data(text: &str) -> String {
+    text.replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+fn escape_annotation_property(text: &str) -> String {
+    escape_annotation_data(text)
+        .replace(':', "%3A")
+        .replace(',', "%2C")
+}
+
+/// `file` relative to `root`, with forward slashes on every platform. Files outside `root` keep
+/// their path.
+fn relative_uri(file: &str, root: impl AsRef<Path>) -> String {
+    let path = Path::new(file);
+    let Ok(relative) = path.strip_prefix(root) else {
+        return file.replace('\\', "/");
+    };
+    let components: Vec<_> = relative
+        .components()
+        .filter_map(|component| match component {
+            std::path::Component::Normal(part) => part.to_str(),
+            std::path::Component::ParentDir => Some(".."),
+            _ => None,
+        })
+        .collect();
+    components.join("/")
+}
+
+/// Writes a workbook with one worksheet: a bold, filterable header row, unless `header` is `None`,
+/// and a row per entry, with line numbers as numbers. The workbook is saved by
+/// [`TodoWriter::finish`].
+#[cfg(feature = "xlsx")]
+pub struct XlsxWriter {
+    path: PathBuf,
+    columns: Vec<Column>,
+    header: Option<Vec<String>>,
+    worksheet: rust_xlsxwriter::Worksheet,
+    rows: u32,
+}
+
+/// The most characters Excel allows in a cell.
+#[cfg(feature = "xlsx")]
+const XLSX_MAX_STRING_LEN: usize = 32_767;
+
+#[cfg(feature = "xlsx")]
+impl XlsxWriter {
+    pub fn new(path: &Path, columns: Vec<Column>, header: Option<Vec<String>>) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            columns,
+            rows: u32::from(header.is_some()),
+            header,
+            worksheet: rust_xlsxwriter::Worksheet::new(),
+        }
+    }
+}
+
+#[cfg(feature = "xlsx")]
+impl TodoWriter for XlsxWriter {
+    fn write_entry(&mut self, entry: &TodoEntry) -> io::Result<()> {
+        for (index, column) in self.columns.iter().enumerate() {
+            let column_index = index as u16;
+            let written = match column {
+                Column::Line if entry.cell.is_none() => {
+                    self.worksheet
+                        .write_number(self.rows, column_index, entry.line as f64)
+                }
+                _ => {
+                    let mut value = column.value(entry);
+                    if let Some((end, _)) = value.char_indices().nth(XLSX_MAX_STRING_LEN) {
+                        log::warn!(
+                            "truncating the {} of {}:{} to the {} characters a cell can hold",
+                            column.name(),
+                            entry.file,
+                            entry.location(),
+                            XLSX_MAX_STRING_LEN
+                        );
+                        value.truncate(end);
+                    }
+                    self.worksheet.write_string(self.rows, column_index, value)
+                }
+            };
+            written.map_err(io::Error::other)?;
+        }
+        self.rows += 1;
+        Ok(())
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        if let Some(header) = &self.header {
+            let bold = rust_xlsxwriter::Format::new().set_bold();
+            for (index, name) in header.iter().enumerate() {
+                self.worksheet
+                    .write_string_with_format(0, index as u16, name, &bold)
+                    .map_err(io::Error::other)?;
+            }
+            let last_row = self.rows.saturating_sub(1);
+            let last_column = self.columns.len().saturating_sub(1) as u16;
+            self.worksheet
+                .autofilter(0, 0, last_row, last_column)
+                .map_err(io::Error::other)?;
+        }
+
+        let mut workbook = rust_xlsxwriter::Workbook::new();
+        workbook.push_worksheet(std::mem::take(&mut self.worksheet));
+        workbook.save(&self.path).map_err(io::Error::other)
+    }
+}
+
+/// Appends a run to the `runs` table and its entries to the `todos` table, creating the schema if
+/// it is missing. Each run is a single transaction, committed by [`TodoWriter::finish`].
+#[cfg(feature = "sqlite")]
+pub struct SqliteWriter {
+    connection: rusqlite::Connection,
+    run_id: i64,
+}
+
+#[cfg(feature = "sqlite")]
+impl SqliteWriter {
+    pub fn open(path: &Path, root: &str) -> io::Result<Self> {
+        let connection = rusqlite::Connection::open(path).map_err(io::Error::other)?;
+        connection
+            .execute_batch(
+                "CREATE TABLE IF NOT EXISTS runs (
+                     id INTEGER PRIMARY KEY,
+                     timestamp TEXT NOT NULL,
+                     root TEXT NOT NULL
+                 );
+                 CREATE TABLE IF NOT EXISTS todos (
+                     id INTEGER PRIMARY KEY,
+                     run_id INTEGER NOT NULL REFERENCES runs(id),
+                     file TEXT NOT NULL,
+                     line INTEGER NOT NULL,
+                     tag TEXT NOT NULL,
+                     comment TEXT NOT NULL
+                 );
+                 BEGIN;",
+            )
+            .map_err(io::Error::other)?;
+        connection
+            .execute(
+                "INSERT INTO runs (timestamp, root) VALUES (?1, ?2)",
+                (chrono::Local::now().to_rfc3339(), root),
+            )
+            .map_err(io::Error::other)?;
+        let run_id = connection.last_insert_rowid();
+        Ok(Self { connection, run_id })
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl TodoWriter for SqliteWriter {
+    fn write_entry(&mut self, entry: &TodoEntry) -> io::Result<()> {
+        self.connection
+            .prepare_cached(
+                "INSERT INTO todos (run_id, file, line, tag, comment) VALUES (?1, ?2, ?3, ?4, ?5)",
+            )
+            .and_then(|mut statement| {
+                statement.execute((
+                    self.run_id,
+                    &entry.file,
+                    entry.line as i64,
+                    &entry.todo.tag,
+                    &entry.todo.comment,
+                ))
+            })
+            .map_err(io::Error::other)?;
+        Ok(())
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        self.connection
+            .execute_batch("COMMIT;")
+            .map_err(io::Error::other)
+    }
+}
+
+const HTML_TEMPLATE: &str = include_str!("report.html");
+
+/// Escapes text for HTML and XML content and attribute values.
+fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Backslash-escapes the characters that would otherwise start links, code spans, emphasis,
+/// tables or HTML.
+fn escape_markdown(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(
+            c,
+            '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '>' | '|' | '#' | '~' | '!'
+        ) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
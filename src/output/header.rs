@@ -0,0 +1,87 @@
+//! The header row of the tabular formats, which an appended CSV file must already have.
+
+use std::fs::File;
+use std::io;
+use std::path::Path;
+
+use super::Column;
+
+/// The header row of the tabular formats.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum Header {
+    /// The column names, such as `File` and `Line`.
+    #[default]
+    Columns,
+    /// No header row.
+    Omit,
+    /// These names, one per column.
+    Custom(Vec<String>),
+}
+
+impl Header {
+    /// The header asked for with `--no-header`, or with `--header` and its comma-separated
+    /// names.
+    pub fn from_flags(omit: bool, names: Option<&str>) -> Self {
+        match names {
+            _ if omit => Self::Omit,
+            Some(names) => Self::Custom(names.split(',').map(|n| n.trim().to_string()).collect()),
+            None => Self::Columns,
+        }
+    }
+
+    /// The row to write above `columns`, or `None` for [`Header::Omit`]. Fails if a custom
+    /// header doesn't have a name per column.
+    pub fn names(&self, columns: &[Column]) -> io::Result<Option<Vec<String>>> {
+        match self {
+            Self::Columns => Ok(Some(columns.iter().map(|c| c.name().to_string()).collect())),
+            Self::Omit => Ok(None),
+            Self::Custom(names) if names.len() == columns.len() => Ok(Some(names.clone())),
+            Self::Custom(names) => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "the header has {} names but there are {} columns",
+                    names.len(),
+                    columns.len()
+                ),
+            )),
+        }
+    }
+
+    /// Whether the CSV file at `path`, about to be appended to, still needs this header: it
+    /// does when the file is missing or empty. Fails if its first row is another header, so
+    /// runs with different columns don't end up in one file. That can't be checked for
+    /// [`Header::Omit`].
+    pub fn needed_in(&self, path: &Path, columns: &[Column], delimiter: u8) -> io::Result<bool> {
+        let names = self.names(columns)?;
+        let file = match File::open(path) {
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(names.is_some()),
+            file => file?,
+        };
+        if file.metadata()?.len() == 0 {
+            return Ok(names.is_some());
+        }
+        let Some(names) = names else {
+            return Ok(false);
+        };
+        let mut existing = csv::StringRecord::new();
+        csv::ReaderBuilder::new()
+            .delimiter(delimiter)
+            .has_headers(false)
+            .flexible(true)
+            .from_reader(file)
+            .read_record(&mut existing)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err.to_string()))?;
+        if existing.iter().eq(&names) {
+            return Ok(false);
+        }
+        Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "{} has the columns {} but this run writes {}",
+                path.display(),
+                existing.iter().collect::<Vec<_>>().join(","),
+                names.join(",")
+            ),
+        ))
+    }
+}
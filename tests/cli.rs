@@ -0,0 +1,997 @@
+use assert_cmd::Command;
+use predicates::prelude::*;
+use std::fs;
+use std::path::Path;
+
+fn todo_finder() -> Command {
+    Command::cargo_bin("todo").unwrap()
+}
+
+#[test]
+fn test_help() {
+    todo_finder()
+        .arg("--help")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("<PATH>... <output.csv|->"))
+        .stdout(predicate::str::contains("--format <format>"));
+}
+
+#[test]
+fn test_missing_arguments() {
+    todo_finder()
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "required arguments were not provided",
+        ));
+    todo_finder()
+        .arg("src")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("<output.csv|->"));
+}
+
+#[test]
+fn test_unknown_flag_suggests_a_similar_one() {
+    todo_finder()
+        .args(["src", "-", "--formt", "json"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "similar argument exists: '--format'",
+        ));
+}
+
+#[test]
+fn test_scan() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("main.rs"), "// TODO: write main\n").unwrap();
+    let output = dir.path().join("todos.csv");
+
+    todo_finder()
+        .arg(dir.path())
+        .arg(&output)
+        .assert()
+        .success()
+        .stdout(format!("Results saved to: {}\n", output.display()))
+        .stderr(predicate::str::starts_with("Scanned 1 files in "));
+
+    assert_eq!(
+        fs::read_to_string(&output).unwrap(),
+        format!(
+            "File,Line,Tag,Author,Issue,Priority,Due,Comment\n{},1,TODO,,,,,write main\n",
+            dir.path().join("main.rs").display()
+        )
+    );
+}
+
+/// Scans `paths` with `args` into a CSV of the Comment column, whose rows are returned sorted.
+fn scan_comments(paths: &[&Path], args: &[&str]) -> Vec<String> {
+    let dir = tempfile::tempdir().unwrap();
+    let output = dir.path().join("todos.csv");
+    todo_finder()
+        .args(paths)
+        .arg("--output")
+        .arg(&output)
+        .args(args)
+        .args(["--columns", "comment", "--no-header"])
+        .assert()
+        .success();
+    let mut comments: Vec<_> = fs::read_to_string(&output)
+        .unwrap()
+        .lines()
+        .map(str::to_string)
+        .collect();
+    comments.sort();
+    comments
+}
+
+#[test]
+fn test_scan_multiple_roots() {
+    let dir = tempfile::tempdir().unwrap();
+    let core = dir.path().join("core");
+    let cli = dir.path().join("cli");
+    let nested = core.join("nested");
+    fs::create_dir_all(&nested).unwrap();
+    fs::create_dir(&cli).unwrap();
+    fs::write(core.join("lib.rs"), "// TODO: core\n").unwrap();
+    fs::write(nested.join("mod.rs"), "// TODO: nested\n").unwrap();
+    fs::write(cli.join("main.rs"), "// TODO: cli\n").unwrap();
+
+    assert_eq!(
+        scan_comments(&[&core, &cli], &[]),
+        ["cli", "core", "nested"]
+    );
+    assert_eq!(scan_comments(&[&core, &nested], &[]), ["core", "nested"]);
+    assert_eq!(
+        scan_comments(&[&nested, &core, &core], &[]),
+        ["core", "nested"]
+    );
+
+    // Without --output, the last path is the output.
+    let output = dir.path().join("out.csv");
+    todo_finder()
+        .arg(&core)
+        .arg(&cli)
+        .arg(&output)
+        .assert()
+        .success();
+    assert_eq!(fs::read_to_string(&output).unwrap().lines().count(), 4);
+}
+
+#[test]
+fn test_path_style_multiple_roots() {
+    let dir = tempfile::tempdir().unwrap();
+    let (a, b) = (dir.path().join("a"), dir.path().join("b"));
+    fs::create_dir_all(a.join("src")).unwrap();
+    fs::create_dir(&b).unwrap();
+    fs::write(a.join("src").join("x.lua"), "-- TODO: in a\n").unwrap();
+    fs::write(b.join("z.lua"), "-- TODO: in b\n").unwrap();
+    fs::write(b.join("x.lua"), "-- TODO: also in b\n").unwrap();
+    let scan = |args: &[&str]| {
+        let output = todo_finder()
+            .arg(&a)
+            .arg(&b)
+            .arg("-")
+            .args(args)
+            .assert()
+            .success();
+        String::from_utf8(output.get_output().stdout.clone()).unwrap()
+    };
+
+    let files = scan(&[
+        "--path-style",
+        "relative",
+        "--columns",
+        "file",
+        "--no-header",
+    ]);
+    // Paths start from the directory the roots share, so same-named files stay apart.
+    let expected = [
+        Path::new("a").join("src").join("x.lua"),
+        Path::new("b").join("x.lua"),
+        Path::new("b").join("z.lua"),
+    ]
+    .map(|path| format!("{}\n", path.display()));
+    assert_eq!(files, expected.concat());
+    // So do the SARIF URIs, which %SRCROOT% resolves from that directory.
+    let sarif: serde_json::Value = serde_json::from_str(&scan(&["--format", "sarif"])).unwrap();
+    let uris: Vec<_> = sarif["runs"][0]["results"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|result| &result["locations"][0]["physicalLocation"]["artifactLocation"]["uri"])
+        .collect();
+    assert_eq!(uris, ["a/src/x.lua", "b/x.lua", "b/z.lua"]);
+    // JUnit has a suite per root, however the paths are spelled.
+    for style in ["as-given", "relative", "absolute"] {
+        let junit = scan(&["--format", "junit", "--path-style", style]);
+        let suites: Vec<_> = junit
+            .lines()
+            .filter(|line| line.contains("<testsuite "))
+            .map(|line| line.split('"').nth(3).unwrap())
+            .collect();
+        assert_eq!(suites, ["1", "2"], "{}", junit);
+        assert!(junit.contains(&format!("<testsuite name=\"{}\"", b.display())));
+    }
+}
+
+#[test]
+fn test_rust_macros_follow_tags() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(
+        dir.path().join("a.rs"),
+        "fn f() { todo!(\"wire up\") }\nfn g() { unimplemented!(\"later\") }\n// FIXME: real\n",
+    )
+    .unwrap();
+
+    assert_eq!(scan_comments(&[dir.path()], &["--tags", "FIXME"]), ["real"]);
+    assert_eq!(
+        scan_comments(&[dir.path()], &["--tags", "todo", "--ignore-case"]),
+        ["later", "wire up"]
+    );
+}
+
+#[test]
+fn test_scan_files_and_directories() {
+    let dir = tempfile::tempdir().unwrap();
+    let src = dir.path().join("src");
+    fs::create_dir(&src).unwrap();
+    fs::write(src.join("lib.rs"), "// TODO: lib\n").unwrap();
+    fs::write(src.join("main.rs"), "// TODO: main\n").unwrap();
+    let tools = dir.path().join("tools");
+    fs::create_dir(&tools).unwrap();
+    fs::write(tools.join("build.sh"), "# TODO: build\n").unwrap();
+    let hook = dir.path().join("pre-commit");
+    fs::write(&hook, "# TODO: hook\n").unwrap();
+
+    assert_eq!(scan_comments(&[&src.join("lib.rs")], &[]), ["lib"]);
+    assert_eq!(
+        scan_comments(&[&src.join("main.rs"), &tools, &src], &[]),
+        ["build", "lib", "main"]
+    );
+    // Files in no supported language are skipped unless a language is forced.
+    assert_eq!(scan_comments(&[&hook, &tools], &[]), ["build"]);
+
+    let output = dir.path().join("out.csv");
+    todo_finder()
+        .arg(&hook)
+        .arg(&output)
+        .args(["--force-language", "shell", "--columns", "language,comment"])
+        .assert()
+        .success();
+    assert_eq!(
+        fs::read_to_string(&output).unwrap(),
+        "Language,Comment\nShell,hook\n"
+    );
+}
+
+#[test]
+fn test_reader_closing_early() {
+    use std::io::{BufRead, BufReader};
+    use std::process::{Command, Stdio};
+
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("big.rs"), "// TODO: again\n".repeat(50_000)).unwrap();
+    let mut child = Command::new(assert_cmd::cargo::cargo_bin("todo"))
+        .arg(dir.path().join("big.rs"))
+        .arg("-")
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    // Read the header, as `head -1` would, and hang up.
+    let mut header = String::new();
+    BufReader::new(child.stdout.take().unwrap())
+        .read_line(&mut header)
+        .unwrap();
+    assert!(header.starts_with("File,Line"));
+
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8(output.stderr).unwrap(), "");
+}
+
+#[test]
+fn test_source_file_is_not_taken_as_output() {
+    let dir = tempfile::tempdir().unwrap();
+    let (a, keep) = (dir.path().join("a.rs"), dir.path().join("keep.rs"));
+    fs::write(&a, "// TODO: one\n").unwrap();
+    fs::write(&keep, "// TODO: two\n").unwrap();
+
+    todo_finder()
+        .arg(&a)
+        .arg(&keep)
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("give it with --output"));
+    assert_eq!(fs::read_to_string(&keep).unwrap(), "// TODO: two\n");
+    // An earlier report can still be replaced, and --output overwrites anything.
+    let report = dir.path().join("todos.md");
+    fs::write(&report, "# old\n").unwrap();
+    for _ in 0..2 {
+        todo_finder()
+            .arg(&a)
+            .arg(&report)
+            .args(["--format", "markdown"])
+            .assert()
+            .success();
+    }
+    todo_finder()
+        .arg(&a)
+        .arg("--output")
+        .arg(&keep)
+        .assert()
+        .success();
+    assert!(fs::read_to_string(&keep).unwrap().starts_with("File,"));
+}
+
+#[test]
+fn test_missing_path() {
+    let dir = tempfile::tempdir().unwrap();
+    let missing = dir.path().join("missing.rs");
+    todo_finder()
+        .arg(&missing)
+        .arg(dir.path().join("out.csv"))
+        .assert()
+        .failure()
+        .stderr(format!(
+            "No such file or directory: {}\n",
+            missing.display()
+        ));
+    assert!(!dir.path().join("out.csv").exists());
+}
+
+#[test]
+fn test_files_from() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("lib.rs"), "// TODO: lib\n").unwrap();
+    fs::write(dir.path().join("notes.txt"), "# TODO: notes\n").unwrap();
+    fs::write(dir.path().join("odd\nname.py"), "# TODO: odd\n").unwrap();
+    fs::write(dir.path().join(".gitignore"), "lib.rs\n").unwrap();
+    let output = dir.path().join("out.csv");
+    let scan = |args: &[&str], stdin: &str| {
+        todo_finder()
+            .current_dir(dir.path())
+            .args(args)
+            .arg(&output)
+            .args(["--columns", "file,comment", "--no-header"])
+            .write_stdin(stdin)
+            .assert()
+            .success()
+            .stderr(predicate::str::contains(
+                "Warning: skipping missing.rs, which doesn't exist",
+            ));
+        fs::read_to_string(&output).unwrap()
+    };
+
+    // Ignore files don't apply to listed paths.
+    assert_eq!(
+        scan(
+            &["--files-from", "-"],
+            "lib.rs\r\nnotes.txt\nmissing.rs\n\n"
+        ),
+        "lib.rs,lib\n"
+    );
+    assert_eq!(
+        scan(
+            &["--files-from", "-", "--no-filter"],
+            "lib.rs\nnotes.txt\nmissing.rs\n"
+        ),
+        "lib.rs,lib\nnotes.txt,notes\n"
+    );
+    assert_eq!(
+        scan(&["--files-from0", "-"], "odd\nname.py\0missing.rs\0"),
+        "\"odd\nname.py\",odd\n"
+    );
+    fs::write(dir.path().join("list"), "missing.rs\nlib.rs\n").unwrap();
+    assert_eq!(scan(&["--files-from", "list"], ""), "lib.rs,lib\n");
+
+    todo_finder()
+        .current_dir(dir.path())
+        .args(["--files-from", "-", "src", "out.csv"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("can't be given with --files-from"));
+}
+
+#[test]
+fn test_exclude() {
+    let dir = tempfile::tempdir().unwrap();
+    let core = dir.path().join("core");
+    let cli = dir.path().join("cli");
+    for root in [&core, &cli] {
+        fs::create_dir_all(root.join("generated").join("deep")).unwrap();
+        fs::create_dir_all(root.join("migrations")).unwrap();
+        let name = root.file_name().unwrap().to_str().unwrap();
+        let todo = |what: &str| format!("// TODO: {} {}\n", name, what);
+        fs::write(root.join("lib.rs"), todo("lib")).unwrap();
+        fs::write(root.join("api.pb.rs"), todo("pb")).unwrap();
+        fs::write(
+            root.join("generated").join("deep").join("out.rs"),
+            todo("generated"),
+        )
+        .unwrap();
+        fs::write(
+            root.join("migrations").join("0001.py"),
+            "# TODO: migration\n",
+        )
+        .unwrap();
+    }
+    // A file named like a directory pattern is kept.
+    fs::write(core.join("generated.rs"), "// TODO: core generated.rs\n").unwrap();
+    let scan = |roots: &[&Path], excludes: &[&str]| {
+        let args: Vec<_> = excludes
+            .iter()
+            .flat_map(|glob| ["--exclude", glob])
+            .collect();
+        scan_comments(roots, &args)
+    };
+
+    assert_eq!(
+        scan(
+            &[&core],
+            &["**/generated/**", "*.pb.rs", "**/migrations/*.py"]
+        ),
+        ["core generated.rs", "core lib"]
+    );
+    assert_eq!(
+        scan(&[&core, &cli], &["generated/", "*.pb.rs", "migrations/"]),
+        ["cli lib", "core generated.rs", "core lib"]
+    );
+    // Globs are matched against the path relative to each root.
+    assert_eq!(
+        scan(&[&core, &cli], &["/lib.rs", "generated", "*.pb.rs", "*.py"]),
+        ["core generated.rs"]
+    );
+
+    todo_finder()
+        .args(["src", "-", "--exclude", "a/**{"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "error: invalid --exclude glob 'a/**{'",
+        ));
+}
+
+#[test]
+fn test_include() {
+    let dir = tempfile::tempdir().unwrap();
+    let files = [
+        "src/lib.rs",
+        "src/generated/out.rs",
+        "crates/core/src/lib.rs",
+        "crates/core/benches/bench.rs",
+        "build.rs",
+        "src/notes.txt",
+    ];
+    for file in files {
+        let path = dir.path().join(file);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, format!("// TODO: {}\n", file)).unwrap();
+    }
+    let scan = |args: &[&str]| scan_comments(&[dir.path()], args);
+
+    // The extension check still applies to included files.
+    assert_eq!(
+        scan(&["--include", "src/**", "--include", "crates/*/src/**"]),
+        [
+            "crates/core/src/lib.rs",
+            "src/generated/out.rs",
+            "src/lib.rs"
+        ]
+    );
+    // Excludes win over includes, whatever the order of the flags.
+    assert_eq!(
+        scan(&["--exclude", "generated/", "--include", "src/**"]),
+        ["src/lib.rs"]
+    );
+    assert_eq!(
+        scan(&["--include", "**/lib.rs", "--exclude", "crates/"]),
+        ["src/lib.rs"]
+    );
+
+    todo_finder()
+        .args(["src", "-", "--include", "a/**{"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "error: invalid --include glob 'a/**{': unclosed alternate group",
+        ));
+}
+
+#[test]
+fn test_extensions() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("api.proto"), "// TODO: proto\n").unwrap();
+    fs::write(dir.path().join("lib.rs"), "// TODO: rust\n").unwrap();
+    fs::write(dir.path().join("main.py"), "# TODO: python\n").unwrap();
+    let scan = |args: &[&str]| scan_comments(&[dir.path()], args);
+
+    assert_eq!(scan(&[]), ["python", "rust"]);
+    assert_eq!(
+        scan(&["--add-extensions", "proto=slashes"]),
+        ["proto", "python", "rust"]
+    );
+    assert_eq!(scan(&["--extensions", "rs"]), ["rust"]);
+    assert_eq!(
+        scan(&[
+            "--extensions",
+            "rs,proto",
+            "--add-extensions",
+            "proto=slashes"
+        ]),
+        ["proto", "rust"]
+    );
+
+    todo_finder()
+        .args(["src", "-", "--add-extensions", "proto=bang"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("unknown comment syntax \"bang\""));
+    todo_finder()
+        .args(["src", "-", "--extensions", "rs,proto"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "unknown extension \"proto\" in --extensions",
+        ));
+}
+
+#[test]
+fn test_ignore_flags() {
+    let dir = tempfile::tempdir().unwrap();
+    let status = std::process::Command::new("git")
+        .arg("init")
+        .arg("-q")
+        .arg(dir.path())
+        .status()
+        .unwrap();
+    assert!(status.success());
+    fs::write(dir.path().join(".gitignore"), "scratch/\n").unwrap();
+    fs::write(dir.path().join(".ignore"), "vendor/\n").unwrap();
+    for (file, comment) in [
+        ("src/lib.rs", "src"),
+        ("scratch/notes.rs", "scratch"),
+        ("vendor/dep.rs", "vendor"),
+        (".config/hidden.rs", "hidden"),
+    ] {
+        let path = dir.path().join(file);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, format!("// TODO: {}\n", comment)).unwrap();
+    }
+    let scan = |args: &[&str]| scan_comments(&[dir.path()], args);
+
+    assert_eq!(scan(&[]), ["src"]);
+    assert_eq!(scan(&["--no-ignore-vcs"]), ["scratch", "src"]);
+    assert_eq!(scan(&["--no-ignore"]), ["scratch", "src", "vendor"]);
+    assert_eq!(scan(&["--hidden"]), ["hidden", "src"]);
+    assert_eq!(
+        scan(&["--no-ignore", "--hidden"]),
+        ["hidden", "scratch", "src", "vendor"]
+    );
+}
+
+#[test]
+#[cfg(unix)]
+fn test_follow_symlinks() {
+    use std::os::unix::fs::symlink;
+
+    let dir = tempfile::tempdir().unwrap();
+    let common = dir.path().join("common");
+    let service = dir.path().join("services").join("api");
+    fs::create_dir_all(&common).unwrap();
+    fs::create_dir_all(&service).unwrap();
+    fs::write(common.join("util.rs"), "// TODO: shared\n").unwrap();
+    fs::write(service.join("main.rs"), "// TODO: api\n").unwrap();
+    symlink(&common, service.join("common")).unwrap();
+    // A cycle back to the top of the tree.
+    symlink(dir.path(), common.join("loop")).unwrap();
+
+    assert_eq!(scan_comments(&[&service], &[]), ["api"]);
+    assert_eq!(scan_comments(&[&service], &["--follow"]), ["api", "shared"]);
+    // The shared file is reached through both its real path and the link.
+    assert_eq!(
+        scan_comments(&[dir.path()], &["--follow-symlinks"]),
+        ["api", "shared"]
+    );
+    todo_finder()
+        .arg(dir.path())
+        .arg(dir.path().join("out.csv"))
+        .arg("--follow")
+        .timeout(std::time::Duration::from_secs(30))
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("Warning: File system loop found"));
+}
+
+#[test]
+fn test_max_depth() {
+    let dir = tempfile::tempdir().unwrap();
+    let core = dir.path().join("core");
+    let cli = dir.path().join("tools").join("cli");
+    for (root, name) in [(&core, "core"), (&cli, "cli")] {
+        let deep = root.join("a").join("b");
+        fs::create_dir_all(&deep).unwrap();
+        fs::write(root.join("top.rs"), format!("// TODO: {} 1\n", name)).unwrap();
+        fs::write(
+            root.join("a").join("mid.rs"),
+            format!("// TODO: {} 2\n", name),
+        )
+        .unwrap();
+        fs::write(deep.join("low.rs"), format!("// TODO: {} 3\n", name)).unwrap();
+    }
+    let scan = |depth: &str| scan_comments(&[&core, &cli], &["--max-depth", depth]);
+
+    // The depth counts from each root, which is itself at depth 0.
+    assert_eq!(scan("0"), Vec::<String>::new());
+    assert_eq!(scan("1"), ["cli 1", "core 1"]);
+    assert_eq!(scan("2"), ["cli 1", "cli 2", "core 1", "core 2"]);
+    assert_eq!(scan_comments(&[&core, &cli], &[]).len(), 6);
+    // Files given as inputs are scanned whatever the depth.
+    assert_eq!(
+        scan_comments(&[&core.join("a").join("mid.rs")], &["--max-depth", "0"]),
+        ["core 2"]
+    );
+}
+
+#[test]
+fn test_config_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let src = dir.path().join("src");
+    fs::create_dir_all(src.join("generated")).unwrap();
+    fs::write(src.join("lib.rs"), "// TODO: todo\n// HACK: hack\n").unwrap();
+    fs::write(src.join("generated").join("api.rs"), "// HACK: generated\n").unwrap();
+    let config = dir.path().join("todo2csv.toml");
+    fs::write(
+        &config,
+        "keywords = [\"HACK\"]\nexclude = [\"generated/**\"]\nformat = \"grep\"\n",
+    )
+    .unwrap();
+
+    // The file is found above the scan root, and --format wins over its grep format.
+    let scan = |args: &[&str]| scan_comments(&[&src], &[&["--format", "csv"], args].concat());
+    assert_eq!(scan(&[]), ["hack"]);
+    assert_eq!(scan(&["--tags", "TODO"]), ["todo"]);
+    assert_eq!(scan(&["--exclude", "*.txt"]), ["generated", "hack"]);
+    assert_eq!(scan(&["--no-config"]), ["generated", "hack", "todo"]);
+    todo_finder()
+        .arg(&src)
+        .arg("-")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("lib.rs:2:"));
+
+    let other = dir.path().join("other.toml");
+    fs::write(&other, "keywords = [\"TODO\"]\n").unwrap();
+    assert_eq!(scan(&["--config", other.to_str().unwrap()]), ["todo"]);
+
+    fs::write(
+        &config,
+        "keywords = [\"HACK\"]\nexcludes = [\"generated/**\"]\n",
+    )
+    .unwrap();
+    todo_finder()
+        .arg(&src)
+        .arg("-")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "todo2csv.toml: TOML parse error at line 2",
+        ))
+        .stderr(predicate::str::contains("unknown field `excludes`"));
+}
+
+#[test]
+fn test_append() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("main.rs"), "// TODO: write main\n").unwrap();
+    let output = dir.path().join("todos.csv");
+    let append = |args: &[&str]| {
+        todo_finder()
+            .arg(dir.path().join("main.rs"))
+            .arg(&output)
+            .args(["--append", "--columns", "line,comment"])
+            .args(args)
+            .assert()
+    };
+
+    append(&[]).success();
+    append(&[]).success();
+    assert_eq!(
+        fs::read_to_string(&output).unwrap(),
+        "Line,Comment\n1,write main\n1,write main\n"
+    );
+    append(&["--header", "line,text"])
+        .code(1)
+        .stderr(predicate::str::starts_with("error: "))
+        .stderr(predicate::str::contains(
+            "has the columns Line,Comment but this run writes line,text",
+        ));
+    append(&["--format", "json"])
+        .code(1)
+        .stderr("error: only csv output can be appended to\n");
+}
+
+#[test]
+fn test_quiet_and_verbose() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("main.rs"), "// TODO: one\n// TODO: two\n").unwrap();
+    fs::write(dir.path().join("notes.txt"), "TODO: not code\n").unwrap();
+    fs::write(dir.path().join("lib.rs"), "").unwrap();
+    let output = dir.path().join("todos.csv");
+    let run = |args: &[&str]| {
+        todo_finder()
+            .arg(dir.path())
+            .arg(&output)
+            .args(args)
+            .assert()
+    };
+
+    run(&["--quiet"]).success().stdout("").stderr("");
+    let main = dir.path().join("main.rs");
+    run(&["-v"])
+        .success()
+        .stderr(predicate::str::contains(format!(
+            "{}: 2 TODOs\n",
+            main.display()
+        )))
+        .stderr(predicate::str::contains(format!(
+            "{}: 0 TODOs\n",
+            dir.path().join("lib.rs").display()
+        )))
+        .stderr(predicate::str::contains(format!(
+            "skipping {}: no supported language\n",
+            dir.path().join("notes.txt").display()
+        )))
+        .stderr(predicate::str::contains("Scanned 2 files in "));
+    // Diagnostics stay out of output written to stdout.
+    todo_finder()
+        .arg(&main)
+        .args(["-", "-v", "--columns", "comment", "--no-header"])
+        .assert()
+        .success()
+        .stdout("one\ntwo\n");
+    // Warnings go too, but not errors.
+    todo_finder()
+        .arg(dir.path().join("notes.txt"))
+        .arg(&output)
+        .arg("-q")
+        .assert()
+        .success()
+        .stderr("");
+    todo_finder()
+        .args(["missing.rs", "-", "-q"])
+        .assert()
+        .failure()
+        .stderr("No such file or directory: missing.rs\n");
+}
+
+#[test]
+fn test_progress() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("main.rs"), "// TODO: write main\n").unwrap();
+    let output = dir.path().join("todos.csv");
+
+    // Without a terminal there is no progress to show, so the summary comes first.
+    for args in [
+        &[][..],
+        &["--progress", "bar"],
+        &["--progress", "spinner", "-v"],
+    ] {
+        todo_finder()
+            .arg(dir.path())
+            .arg(&output)
+            .args(args)
+            .assert()
+            .success()
+            .stderr(predicate::str::contains("files,").not())
+            .stderr(predicate::str::contains("Scanned 1 files in "));
+    }
+    todo_finder()
+        .args(["src", "-", "--progress", "dots"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("unknown progress style"));
+    todo_finder()
+        .args(["src", "-", "--progress", "bar", "--no-progress"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+}
+
+#[test]
+fn test_fail_if_found() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::create_dir_all(dir.path().join("src")).unwrap();
+    fs::create_dir_all(dir.path().join("scripts")).unwrap();
+    fs::write(dir.path().join("src").join("lib.rs"), "// FIXME: later\n").unwrap();
+    fs::write(dir.path().join("scripts").join("run.py"), "# TODO: retry\n").unwrap();
+    let output = dir.path().join("todos.csv");
+    let run = |args: &[&str]| {
+        todo_finder()
+            .arg(dir.path())
+            .arg(&output)
+            .arg("--fail-if-found")
+            .args(args)
+            .assert()
+    };
+
+    run(&[]).code(3);
+    assert_eq!(fs::read_to_string(&output).unwrap().lines().count(), 3);
+    run(&["--tags", "TODO"]).code(3);
+    // Only the entries left after filtering count.
+    run(&["--tags", "HACK"]).code(0);
+    run(&["--exclude", "scripts/**", "--tags", "TODO"]).code(0);
+    run(&["--include", "src/**", "--tags", "FIXME"]).code(3);
+    todo_finder()
+        .args(["missing", "-", "--fail-if-found"])
+        .assert()
+        .code(1);
+    run(&["--header", "a,b"]).code(1);
+    // Usage errors keep clap's status, so they aren't mistaken for TODOs.
+    run(&["--bogus"]).code(2);
+    run(&["--extensions", "nope"]).code(2);
+}
+
+#[test]
+fn test_max_todos() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(
+        dir.path().join("lib.rs"),
+        "// TODO: one\n// TODO: two\n// FIXME: three\n",
+    )
+    .unwrap();
+    let output = dir.path().join("todos.csv");
+    let run = |args: &[&str]| {
+        todo_finder()
+            .arg(dir.path())
+            .arg(&output)
+            .args(args)
+            .assert()
+    };
+
+    run(&["--max-todos", "3"])
+        .code(0)
+        .stderr(predicate::str::contains("3 TODOs (budget 3)\n"));
+    run(&["--max-todos", "2"])
+        .code(3)
+        .stderr(predicate::str::contains("3 TODOs (budget 2)\n"))
+        .stderr(predicate::str::ends_with("Found 3 TODOs (budget 2)\n"));
+    assert_eq!(fs::read_to_string(&output).unwrap().lines().count(), 4);
+    // FIXMEs can be budgeted on their own.
+    run(&["--max-todos", "1", "--tags", "FIXME"]).code(0);
+    run(&["--max-todos", "1", "--tags", "TODO"])
+        .code(3)
+        .stderr(predicate::str::ends_with("Found 2 TODOs (budget 1)\n"));
+    // --fail-if-found is the stricter budget.
+    run(&["--max-todos", "5", "--fail-if-found"])
+        .code(3)
+        .stderr(predicate::str::ends_with("Found 3 TODOs (budget 0)\n"));
+    run(&["--max-todos", "0", "--quiet", "--tags", "HACK"])
+        .code(0)
+        .stderr("");
+
+    // The stats JSON has the budget, for CI to track.
+    let stats_json = dir.path().join("stats.json");
+    let stats = |args: &[&str]| {
+        todo_finder()
+            .arg(dir.path())
+            .arg(&output)
+            .arg("--stats-json")
+            .arg(&stats_json)
+            .args(args)
+            .output()
+            .unwrap();
+        let stats = fs::read_to_string(&stats_json).unwrap();
+        serde_json::from_str::<serde_json::Value>(&stats).unwrap()
+    };
+    let json = stats(&["--max-todos", "2"]);
+    assert_eq!(json["files_scanned"], 1);
+    assert_eq!(json["budget"], 2);
+    assert_eq!(json["over_budget"], true);
+    let json = stats(&["--max-todos", "3"]);
+    assert_eq!(json["over_budget"], false);
+    let json = stats(&[]);
+    assert!(json["budget"].is_null());
+    assert_eq!(json["over_budget"], false);
+}
+
+#[test]
+fn test_sort() {
+    let dir = tempfile::tempdir().unwrap();
+    for (file, text) in [
+        ("src/b.rs", "// TODO: second\n// FIXME: first\n"),
+        ("src/a/z.rs", "\n// TODO: third\n"),
+        ("src/B.rs", "// HACK: upper\n"),
+    ] {
+        let path = dir.path().join(file);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, text).unwrap();
+    }
+    let scan = |args: &[&str]| {
+        let output = todo_finder()
+            .arg(dir.path().join("src"))
+            .args(["-", "--columns", "line,comment", "--no-header"])
+            .args(args)
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        output.stdout
+    };
+
+    let sorted = scan(&[]);
+    assert_eq!(sorted, scan(&[]));
+    assert_eq!(sorted, scan(&["--sort", "path"]));
+    assert_eq!(
+        String::from_utf8(sorted).unwrap(),
+        "1,upper\n2,third\n1,second\n2,first\n"
+    );
+    assert_eq!(
+        String::from_utf8(scan(&["--sort", "comment"])).unwrap(),
+        "2,first\n1,second\n2,third\n1,upper\n"
+    );
+    let unsorted = String::from_utf8(scan(&["--sort", "none"])).unwrap();
+    assert_eq!(unsorted.lines().count(), 4);
+    todo_finder()
+        .args(["src", "-", "--sort", "size"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("unknown sort order"));
+}
+
+#[test]
+fn test_group_by_file() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::create_dir_all(dir.path().join("src")).unwrap();
+    fs::write(
+        dir.path().join("src").join("lib.rs"),
+        "// TODO: one\n// FIXME: two\n",
+    )
+    .unwrap();
+    fs::write(dir.path().join("build.py"), "# TODO: three\n").unwrap();
+    let scan = |args: &[&str]| {
+        let output = todo_finder()
+            .current_dir(dir.path())
+            .args([".", "-", "--columns", "file,line,tag,comment"])
+            .args(args)
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        String::from_utf8(output.stdout).unwrap()
+    };
+
+    assert_eq!(
+        scan(&["--group-by", "file"]),
+        "File,Line,Tag,Comment\n\
+         ./build.py,1,TODO,three\n\
+         ./build.py,,,TOTAL: 1\n\
+         ./src/lib.rs,1,TODO,one\n\
+         ./src/lib.rs,2,FIXME,two\n\
+         ./src/lib.rs,,,TOTAL: 2\n\
+         ,,,TOTAL: 3\n"
+    );
+    // Without grouping there are no total rows.
+    assert_eq!(
+        scan(&[]),
+        "File,Line,Tag,Comment\n\
+         ./build.py,1,TODO,three\n\
+         ./src/lib.rs,1,TODO,one\n\
+         ./src/lib.rs,2,FIXME,two\n"
+    );
+    let json: serde_json::Value =
+        serde_json::from_str(&scan(&["--group-by", "file", "--format", "json"])).unwrap();
+    assert_eq!(json["./src/lib.rs"][1]["comment"], "two");
+    let html = scan(&["--group-by", "file", "--format", "html"]);
+    assert_eq!(html.matches("<tbody>\n").count(), 2);
+    assert!(html.contains(concat!(
+        "<tbody>\n",
+        "<tr class=\"file\"><th colspan=\"4\"><code>./src/lib.rs</code>: 2 found</th></tr>\n",
+        "<tr><td><code>./src/lib.rs</code></td><td data-sort=\"1\">1</td>",
+    )));
+    assert!(html.contains("<code>.</code>: 3 found."));
+    todo_finder()
+        .args(["src", "-", "--group-by", "file", "--format", "markdown"])
+        .assert()
+        .code(1)
+        .stderr("error: only csv, json and html output can be grouped by file\n");
+}
+
+#[test]
+fn test_count() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("a.rs"), "// TODO: one\n").unwrap();
+    fs::write(dir.path().join("b.rs"), "// TODO: two\n// FIXME: three\n").unwrap();
+    fs::write(dir.path().join("c.rs"), "// HACK: four\n").unwrap();
+    fs::write(dir.path().join("d.rs"), "fn main() {}\n").unwrap();
+    let count = |args: &[&str]| {
+        todo_finder()
+            .current_dir(dir.path())
+            .arg(".")
+            .args(args)
+            .assert()
+    };
+
+    count(&["--count"]).code(0).stdout("4\n").stderr("");
+    count(&["--count", "--tags", "FIXME,HACK"]).stdout("2\n");
+    // The most TODOs first, then by path.
+    count(&["--count-by", "file"])
+        .code(0)
+        .stdout("2\t./b.rs\n1\t./a.rs\n1\t./c.rs\n")
+        .stderr("");
+    count(&["--count", "--fail-if-found"]).code(3).stdout("4\n");
+    count(&["--count", "--max-todos", "4"]).code(0);
+    count(&["--count-by", "file", "--max-todos", "3"])
+        .code(3)
+        .stderr("Found 4 TODOs (budget 3)\n");
+    // No output file is written, and a path to one is scanned instead.
+    count(&["todos.csv", "--count"])
+        .code(1)
+        .stdout("")
+        .stderr("No such file or directory: todos.csv\n");
+    assert!(!dir.path().join("todos.csv").exists());
+    count(&["--count", "--output", "todos.csv"])
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+}
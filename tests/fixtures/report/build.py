@@ -0,0 +1 @@
+# FIXME: it's slow
@@ -0,0 +1,2 @@
+// TODO: escape <script>alert("x")</script> & more
+fn main() {}